@@ -1,6 +1,6 @@
 import { test, expect } from 'vitest'
 
-import { Client } from '../index'
+import { Client, EphemeralDeployment } from '../index'
 
 test('smoke test', async () => {
   let client: Client | null = null
@@ -46,7 +46,20 @@ test('smoke test', async () => {
   expect(getDeployment.name).toBe(createDeploymentOptions.name)
 
   let connString = await client.getConnectionString(createDeploymentOptions.name)
-  expect(connString).toBe(`mongodb://127.0.0.1:${getDeployment.portBindings.port}/?directConnection=true`)
+  expect(connString).toBe(`mongodb://127.0.0.1:${getDeployment.portBindings[0].port}/?directConnection=true`)
+
+  // Get deployment stats
+  let stats = await client.getDeploymentStats(createDeploymentOptions.name)
+  expect(stats.pids).toBeGreaterThan(0)
+  expect(stats.memoryUsage).toBeGreaterThan(0)
+
+  // Verify the running server matches the deployment labels
+  let buildInfo = await client.getBuildInfo(createDeploymentOptions.name)
+  expect(buildInfo.version).toBe(getDeployment.mongodbVersion)
+  expect(buildInfo.versionMatchesLabel).toBe(true)
+
+  let serverStatus = await client.getServerStatus(createDeploymentOptions.name)
+  expect(serverStatus.version).toBe(buildInfo.version)
 
   // Count deployments after creation
   let after_create_deployment_count = (await client.listDeployments()).length
@@ -59,3 +72,41 @@ test('smoke test', async () => {
   let after_delete_deployment_count = (await client.listDeployments()).length
   expect(start_deployments_count).toBe(after_delete_deployment_count)
 })
+
+// Older Node.js versions only have the well-known symbol under its registered name
+const asyncDispose = (Symbol.asyncDispose ?? Symbol.for('nodejs.asyncDispose')) as typeof Symbol.asyncDispose
+
+test('Symbol.asyncDispose calls dispose', async () => {
+  let disposed = 0
+  const deployment = Object.create(EphemeralDeployment.prototype) as EphemeralDeployment
+  deployment.dispose = async () => {
+    disposed += 1
+  }
+
+  await deployment[asyncDispose]()
+
+  expect(disposed).toBe(1)
+})
+
+test('Symbol.asyncDispose deletes an ephemeral deployment', async () => {
+  let client: Client
+  try {
+    client = await Client.connect()
+  } catch (e: any) {
+    expect(e?.message).toBe('connect to docker')
+    return
+  }
+
+  if (process.platform === 'win32') {
+    return
+  }
+
+  const deployment = await client.createEphemeralDeployment({ doNotTrack: true })
+  let names = (await client.listDeployments()).map((d) => d.name)
+  expect(names).toContain(deployment.name)
+
+  await deployment[asyncDispose]()
+
+  names = (await client.listDeployments()).map((d) => d.name)
+  expect(names).not.toContain(deployment.name)
+})
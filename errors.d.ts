@@ -0,0 +1,23 @@
+// Errors thrown by the client that can be told apart, by their `code`
+
+/** Thrown by `createDeployment`, `updateDeployment`, `apply` and the ephemeral deployments when a requested port is taken */
+export declare class PortInUseError extends Error {
+  static readonly code: 'ERR_PORT_IN_USE'
+  code: 'ERR_PORT_IN_USE'
+}
+
+/** Thrown by `renameDeployment` when another container already has the name */
+export declare class DeploymentNameConflictError extends Error {
+  static readonly code: 'ERR_DEPLOYMENT_NAME_CONFLICT'
+  code: 'ERR_DEPLOYMENT_NAME_CONFLICT'
+}
+
+/**
+ * Thrown by `rotateRootPassword` when mongod has the new password but the deployment couldn't be
+ * updated with it, `password` is the only copy of the new password
+ */
+export declare class RootPasswordNotStoredError extends Error {
+  static readonly code: 'ERR_ROOT_PASSWORD_NOT_STORED'
+  code: 'ERR_ROOT_PASSWORD_NOT_STORED'
+  password: string
+}
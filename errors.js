@@ -0,0 +1,30 @@
+// Errors thrown by the client that can be told apart, by their `code`
+// `error instanceof PortInUseError` is true for any error with the matching code
+
+class PortInUseError extends Error {
+  static code = 'ERR_PORT_IN_USE'
+
+  static [Symbol.hasInstance](error) {
+    return error instanceof Error && error.code === PortInUseError.code
+  }
+}
+
+class DeploymentNameConflictError extends Error {
+  static code = 'ERR_DEPLOYMENT_NAME_CONFLICT'
+
+  static [Symbol.hasInstance](error) {
+    return error instanceof Error && error.code === DeploymentNameConflictError.code
+  }
+}
+
+class RootPasswordNotStoredError extends Error {
+  static code = 'ERR_ROOT_PASSWORD_NOT_STORED'
+
+  static [Symbol.hasInstance](error) {
+    return error instanceof Error && error.code === RootPasswordNotStoredError.code
+  }
+}
+
+module.exports.PortInUseError = PortInUseError
+module.exports.DeploymentNameConflictError = DeploymentNameConflictError
+module.exports.RootPasswordNotStoredError = RootPasswordNotStoredError
@@ -2,39 +2,155 @@
 /* eslint-disable */
 export declare class Client {
   static connect(): Client
-  createDeployment(createDeplomentOptions: CreateDeploymentOptions): Promise<Deployment>
-  listDeployments(): Promise<Array<Deployment>>
+  createDeployment(createDeplomentOptions: CreateDeploymentOptions, onSampleDataProgress?: ((arg: SampleDataProgress) => void) | undefined | null): Promise<Deployment>
+  listDeployments(filter?: DeploymentFilter | undefined | null): Promise<Array<Deployment>>
+  updateDeployment(deploymentName: string, patch: DeploymentPatch): Promise<UpdateDeploymentResult>
+  renameDeployment(oldName: string, newName: string): Promise<Deployment>
   deleteDeployment(deploymentName: string): Promise<void>
+  deleteDeployments(filter: DeploymentFilter): Promise<Array<BulkOperationResult>>
+  stopDeployments(filter: DeploymentFilter): Promise<Array<BulkOperationResult>>
+  startDeployments(filter: DeploymentFilter): Promise<Array<BulkOperationResult>>
+  reapExpiredDeployments(options?: ReapOptions | undefined | null): Promise<Array<ReapedDeployment>>
   getDeployment(deploymentName: string): Promise<Deployment>
-  getConnectionString(deploymentName: string): Promise<string>
+  getConnectionString(deploymentName: string, options?: ConnectionStringOptions | undefined | null): Promise<string>
+  rotateRootPassword(deploymentName: string): Promise<RootPasswordRotation>
+  setSecretProvider(provider?: ((arg: string) => Promise<string>) | undefined | null): void
+  setServerParameter(deploymentName: string, key: string, value: any): Promise<void>
+  loadSampleData(deploymentName: string, datasets?: Array<string> | undefined | null, onProgress?: ((arg: SampleDataProgress) => void) | undefined | null): Promise<void>
+  seedDeployment(deploymentName: string, seed: Array<SeedSource>): Promise<void>
+  createUser(deploymentName: string, options: CreateUserOptions): Promise<void>
+  listUsers(deploymentName: string, options?: ListUsersOptions | undefined | null): Promise<Array<DatabaseUser>>
+  updateUser(deploymentName: string, username: string, options: UpdateUserOptions): Promise<void>
+  dropUser(deploymentName: string, username: string, database?: string | undefined | null): Promise<void>
+  createRole(deploymentName: string, options: CreateRoleOptions): Promise<void>
   getDeploymentId(clusterIdOrName: string): Promise<string>
+  getDeploymentStats(deploymentName: string): Promise<DeploymentStats>
+  watchDeploymentStats(deploymentName: string, intervalMs: number): DeploymentStatsWatcher
+  getServerStatus(deploymentName: string): Promise<ServerStatus>
+  getBuildInfo(deploymentName: string): Promise<BuildInfo>
+  listImages(): Promise<Array<Image>>
+  removeImage(tagOrDigest: string, options?: RemoveImageOptions | undefined | null): Promise<void>
+  pruneImages(options?: PruneImagesOptions | undefined | null): Promise<PruneImagesResult>
+  resolveImageTag(tag: string): Promise<ResolvedImageTag>
+  plan(spec: DeploymentSpec): Promise<DeploymentPlan>
+  apply(spec: DeploymentSpec): Promise<DeploymentPlan>
+  exportCompose(names: string | Array<string>): Promise<string>
+  adoptContainer(containerId: string): Promise<Deployment>
+  withEphemeralDeployment(options: CreateDeploymentOptions | undefined | null, callback: ((arg0: Deployment, arg1: string) => Promise<void>)): Promise<void>
+  createEphemeralDeployment(options?: CreateDeploymentOptions | undefined | null): Promise<EphemeralDeployment>
+}
+
+export declare class DeploymentStatsWatcher {
+  next(): Promise<DeploymentStats | null>
+  stop(): void
+}
+
+export declare class EphemeralDeployment {
+  name: string
+  connectionString: string
+  dispose(): Promise<void>
+  [Symbol.asyncDispose](): Promise<void>
+}
+
+export type AddressFamily =  'ipv4'|
+'ipv6';
+
+export interface AuthOptions {
+  generate?: boolean
 }
 
 export type BindingType =  'Loopback'|
+'LoopbackIpv6'|
 'AnyInterface'|
 'Specific';
 
+export interface BuildInfo {
+  version: string
+  gitVersion: string
+  modules: Array<string>
+  mongotVersion?: string
+  labelVersion: string
+  versionMatchesLabel: boolean
+}
+
+export interface BulkOperationResult {
+  name: string
+  success: boolean
+  error?: string
+}
+
+export declare function compareMongoDBVersions(a: string, b: string): number
+
+export type ConnectFrom =  'host'|
+'network';
+
+export interface ConnectionStringOptions {
+  family?: AddressFamily
+  from?: ConnectFrom
+  network?: string
+  replicaSet?: boolean
+}
+
 export interface CreateDeploymentOptions {
   name?: string
   image?: string
   skipPullImage?: boolean
   imageTag?: string
+  imageDigest?: string
   waitUntilHealthy?: boolean
   waitUntilHealthyTimeout?: number
   creationSource?: CreationSource
+  ttl?: number
+  expiresAt?: number
+  removeWhenOwnerExits?: boolean
+  autoRemoveOnExit?: boolean
   localSeedLocation?: string
+  dataVolume?: string
+  /**
+   * Run in order with `docker exec` once the deployment is healthy, after `seed`
+   * Unlike scripts in the init directory of the image, they run after the deployment reports
+   * healthy and on every `createDeployment`, also when `dataVolume` already holds data
+   */
+  initScripts?: Array<InitScript>
+  seed?: Array<SeedSource>
   loadSampleData?: boolean
+  sampleData?: SampleDataOptions
   mongodbInitdbDatabase?: string
   mongodbInitdbRootPasswordFile?: string
   mongodbInitdbRootPassword?: string
+  rootPasswordFromFile?: string
+  rootPasswordFromEnv?: string
+  rootPasswordSecret?: string
   mongodbInitdbRootUsernameFile?: string
   mongodbInitdbRootUsername?: string
+  auth?: AuthOptions
   voyageApiKey?: string
   mongotLogFile?: string
   runnerLogFile?: string
   doNotTrack?: boolean
   telemetryBaseUrl?: string
   mongodbPortBinding?: MongoDBPortBinding
+  additionalPortBindings?: Array<MongoDBPortBinding>
+  mongotPortBinding?: MongoDBPortBinding
+  portRange?: PortRange
+  network?: NetworkOptions
+  oplogSizeMB?: number
+  mongodConfig?: any
+  setParameters?: Record<string, any>
+}
+
+export interface CreateRoleOptions {
+  role: string
+  privileges: Array<Privilege>
+  roles?: Array<RoleReference>
+  database?: string
+}
+
+export interface CreateUserOptions {
+  username: string
+  password: string
+  roles: Array<RoleReference>
+  database?: string
 }
 
 export interface CreationSource {
@@ -48,28 +164,148 @@ export type CreationSourceType =  'AtlasCLI'|
 'MCPServer'|
 'Other';
 
+export interface DatabaseUser {
+  username: string
+  database: string
+  roles: Array<RoleReference>
+}
+
 export interface Deployment {
   containerId: string
   name?: string
   state: State
-  portBindings?: MongoDBPortBinding
+  portBindings: Array<MongoDBPortBinding>
+  mongotPortBindings: Array<MongoDBPortBinding>
+  networks: Array<DeploymentNetwork>
+  internalIp?: string
+  image?: string
+  imageTag?: string
+  imageDigest?: string
+  labels: Record<string, string>
   mongodbType: MongodbType
   mongodbVersion: string
+  replicaSet?: string
+  changeStreamsAvailable?: boolean
+  transactionsAvailable?: boolean
+  oplogSizeMB?: number
+  mongodConfig?: any
+  setParameters?: Record<string, any>
   creationSource?: CreationSource
   localSeedLocation?: string
+  dataVolume?: string
   mongodbInitdbDatabase?: string
   mongodbInitdbRootPasswordFile?: string
   mongodbInitdbRootPassword?: string
   mongodbInitdbRootUsernameFile?: string
   mongodbInitdbRootUsername?: string
   mongodbLoadSampleData?: boolean
+  sampleDatasets: Array<string>
   voyageApiKey?: string
+  initScriptOutputs?: Array<InitScriptOutput>
+  generatedCredentials?: GeneratedCredentials
   mongotLogFile?: string
   runnerLogFile?: string
   doNotTrack: boolean
   telemetryBaseUrl?: string
+  expiresAt?: number
+  ownerPid?: number
+  ownerHostname?: string
+}
+
+export interface DeploymentFilter {
+  state?: State
+  creationSource?: CreationSource
+  labels?: Record<string, string>
+  namePattern?: string
+  all?: boolean
+}
+
+export interface DeploymentNetwork {
+  name: string
+  aliases: Array<string>
+  ipAddress?: string
+}
+
+export interface DeploymentPatch {
+  voyageApiKey?: string
+  doNotTrack?: boolean
+  telemetryBaseUrl?: string
+  resources?: ResourceLimits
+  labels?: Record<string, string>
+  mongodbPortBinding?: MongoDbPortBinding
+}
+
+export interface DeploymentPlan {
+  actions: Array<PlannedAction>
+}
+
+export interface DeploymentSpec {
+  deployments: Array<CreateDeploymentOptions>
+  removeExtra?: boolean
+}
+
+export interface DeploymentStats {
+  cpuPercentage: number
+  memoryUsage: number
+  memoryLimit: number
+  memoryPercentage: number
+  oomKilled: boolean
+  networkRxBytes: number
+  networkTxBytes: number
+  blockReadBytes: number
+  blockWriteBytes: number
+  pids: number
+  dataDiskUsage?: number
+}
+
+export interface GeneratedCredentials {
+  username: string
+  password: string
+}
+
+export interface Image {
+  id: string
+  repository: string
+  tag?: string
+  digest?: string
+  size: number
+  created: number
+  mongodbVersion?: string
+  usedBy: Array<string>
+}
+
+export type ImageTagKind =  'semver'|
+'latest'|
+'preview';
+
+export interface ImportedDeployment {
+  options: CreateDeploymentOptions
+  unsupportedKeys: Array<string>
+}
+
+export interface InitScript {
+  path?: string
+  name?: string
+  content?: string
+  type?: InitScriptType
+}
+
+export interface InitScriptOutput {
+  name: string
+  exitCode: number
+  stdout: string
+  stderr: string
+}
+
+export type InitScriptType =  'js'|
+'sh';
+
+export interface ListUsersOptions {
+  database?: string
 }
 
+export declare function loadDeploymentSpec(path: string): DeploymentSpec
+
 export interface MongoDbPortBinding {
   type: BindingType
   ip: string
@@ -79,6 +315,154 @@ export interface MongoDbPortBinding {
 export type MongodbType =  'Community'|
 'Enterprise';
 
+export interface NetworkOptions {
+  name: string
+  aliases?: Array<string>
+  create?: boolean
+}
+
+export declare function parseComposeService(yaml: string, serviceName?: string | undefined | null): ImportedDeployment
+
+export declare function parseDockerRun(command: string): ImportedDeployment
+
+export declare function parseImageTag(tag: string): ParsedImageTag
+
+export interface ParsedImageTag {
+  kind: ImageTagKind
+  major?: number
+  minor?: number
+  patch?: number
+}
+
+export interface PlannedAction {
+  name: string
+  action: PlannedActionType
+  reasons: Array<string>
+}
+
+export type PlannedActionType =  'Create'|
+'Recreate'|
+'Remove'|
+'Unchanged';
+
+export interface PortRange {
+  from: number
+  to: number
+}
+
+export interface Privilege {
+  database: string
+  collection?: string
+  actions: Array<string>
+}
+
+export interface PruneImagesOptions {
+  keepLatest?: number
+  olderThan?: number
+}
+
+export interface PruneImagesResult {
+  removed: Array<Image>
+  reclaimedBytes: number
+}
+
+export interface ReapedDeployment {
+  name: string
+  reason: ReapReason
+}
+
+export interface ReapOptions {
+  dryRun?: boolean
+  creationSource?: CreationSource
+}
+
+export type ReapReason =  'Expired'|
+'OwnerExited';
+
+export interface RemoveImageOptions {
+  force?: boolean
+}
+
+export interface ResolvedImageTag {
+  tag: string
+  digest: string
+  imageId?: string
+  mongodbVersion?: string
+}
+
+export interface ResourceLimits {
+  memoryBytes?: number
+  cpus?: number
+}
+
+export interface RoleReference {
+  role: string
+  db: string
+}
+
+export interface RootPasswordRotation {
+  password: string
+}
+
+export interface SampleDataOptions {
+  datasets?: Array<string>
+  createSearchIndexes?: boolean
+}
+
+export interface SampleDataProgress {
+  stage: SampleDataStage
+  dataset?: string
+  completed: number
+  total: number
+}
+
+export type SampleDataStage =  'Downloading'|
+'Restoring'|
+'CreatingSearchIndexes'|
+'Done';
+
+export declare function satisfiesVersion(version: string, range: string): boolean
+
+export interface SeedSource {
+  type: SeedType
+  path: string
+  database?: string
+  collection?: string
+  gzip?: boolean
+  headerline?: boolean
+  fields?: Array<string>
+  drop?: boolean
+}
+
+export type SeedType =  'archive'|
+'ejson'|
+'csv';
+
+export interface ServerStatus {
+  host: string
+  process: string
+  version: string
+  uptimeSeconds: number
+  storageEngine: string
+  connections: ServerStatusConnections
+  opcounters: ServerStatusOpcounters
+}
+
+export interface ServerStatusConnections {
+  current: number
+  available: number
+  totalCreated: number
+}
+
+export interface ServerStatusOpcounters {
+  insert: number
+  query: number
+  update: number
+  delete: number
+  getmore: number
+  command: number
+}
+
 export type State =  'Created'|
 'Dead'|
 'Exited'|
@@ -86,3 +470,14 @@ export type State =  'Created'|
 'Removing'|
 'Restarting'|
 'Running';
+
+export interface UpdateDeploymentResult {
+  restarted: boolean
+  deployment: Deployment
+}
+
+export interface UpdateUserOptions {
+  password?: string
+  roles?: Array<RoleReference>
+  database?: string
+}
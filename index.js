@@ -587,7 +587,23 @@ if (!nativeBinding) {
 
 module.exports = nativeBinding
 module.exports.Client = nativeBinding.Client
+module.exports.DeploymentStatsWatcher = nativeBinding.DeploymentStatsWatcher
+module.exports.EphemeralDeployment = nativeBinding.EphemeralDeployment
+module.exports.AddressFamily = nativeBinding.AddressFamily
 module.exports.BindingType = nativeBinding.BindingType
+module.exports.compareMongoDBVersions = nativeBinding.compareMongoDBVersions
+module.exports.ConnectFrom = nativeBinding.ConnectFrom
 module.exports.CreationSourceType = nativeBinding.CreationSourceType
+module.exports.ImageTagKind = nativeBinding.ImageTagKind
+module.exports.InitScriptType = nativeBinding.InitScriptType
+module.exports.loadDeploymentSpec = nativeBinding.loadDeploymentSpec
 module.exports.MongodbType = nativeBinding.MongodbType
+module.exports.parseComposeService = nativeBinding.parseComposeService
+module.exports.parseDockerRun = nativeBinding.parseDockerRun
+module.exports.parseImageTag = nativeBinding.parseImageTag
+module.exports.PlannedActionType = nativeBinding.PlannedActionType
+module.exports.ReapReason = nativeBinding.ReapReason
+module.exports.SampleDataStage = nativeBinding.SampleDataStage
+module.exports.satisfiesVersion = nativeBinding.satisfiesVersion
+module.exports.SeedType = nativeBinding.SeedType
 module.exports.State = nativeBinding.State
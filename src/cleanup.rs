@@ -0,0 +1,137 @@
+use std::collections::BTreeSet;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{Mutex, OnceLock};
+use std::time::Duration;
+
+use atlas_local::Client as AtlasLocalClient;
+use futures_util::future::join_all;
+use napi::bindgen_prelude::{Function, FunctionCallContext};
+use napi::threadsafe_function::{ThreadsafeFunction, ThreadsafeFunctionCallMode};
+use napi::{Env, Status};
+
+// Deployments that are removed when the Node.js process exits
+static REGISTERED_DEPLOYMENTS: Mutex<BTreeSet<String>> = Mutex::new(BTreeSet::new());
+
+// The exit hook is only installed once a deployment is registered, processes that never use
+// `autoRemoveOnExit` keep their signal handling
+// `register` runs off the JavaScript thread, it installs the hook through this function
+static INSTALLER: OnceLock<ThreadsafeFunction<(), (), (), Status, false, true>> = OnceLock::new();
+static HOOK_INSTALLED: AtomicBool = AtomicBool::new(false);
+
+// The removal blocks the exit of the process, deployments still being removed after this long are
+// left behind
+const REMOVE_ON_EXIT_TIMEOUT: Duration = Duration::from_secs(10);
+
+// Runs `cleanup` on normal exit, on uncaught exceptions and on termination signals
+// A signal is raised again after the cleanup when nobody else listens to it, so the default
+// behavior (terminating the process) is kept
+const INSTALL_HOOK_SCRIPT: &str = r#"
+(function (cleanup) {
+  process.once('exit', cleanup)
+  process.on('uncaughtExceptionMonitor', cleanup)
+  for (const signal of ['SIGINT', 'SIGTERM', 'SIGHUP']) {
+    process.once(signal, () => {
+      cleanup()
+      if (process.listenerCount(signal) === 0) {
+        process.kill(process.pid, signal)
+      }
+    })
+  }
+})
+"#;
+
+pub(crate) fn prepare_exit_hook(env: &Env) -> napi::Result<()> {
+  if INSTALLER.get().is_some() {
+    return Ok(());
+  }
+
+  let installer: Function<(), ()> =
+    env.create_function_from_closure("installExitHook", |context: FunctionCallContext| {
+      // A failed install is retried on the next registration
+      if !HOOK_INSTALLED.load(Ordering::SeqCst) && install_exit_hook(context.env).is_ok() {
+        HOOK_INSTALLED.store(true, Ordering::SeqCst);
+      }
+      Ok(())
+    })?;
+  // Weak so that it doesn't keep the process alive
+  let installer = installer
+    .build_threadsafe_function()
+    .callee_handled::<false>()
+    .weak::<true>()
+    .build()?;
+  let _ = INSTALLER.set(installer);
+
+  Ok(())
+}
+
+fn install_exit_hook(env: &Env) -> napi::Result<()> {
+  let install: Function<Function<(), ()>, ()> = env.run_script(INSTALL_HOOK_SCRIPT)?;
+  let cleanup = env.create_function_from_closure("cleanup", |_: FunctionCallContext| {
+    remove_registered_deployments();
+    Ok(())
+  })?;
+
+  install.call(cleanup)
+}
+
+pub(crate) fn register(deployment_name: &str) {
+  if let Ok(mut deployments) = REGISTERED_DEPLOYMENTS.lock() {
+    deployments.insert(deployment_name.to_string());
+  }
+
+  if !HOOK_INSTALLED.load(Ordering::SeqCst) {
+    if let Some(installer) = INSTALLER.get() {
+      installer.call((), ThreadsafeFunctionCallMode::NonBlocking);
+    }
+  }
+}
+
+pub(crate) fn unregister(deployment_name: &str) {
+  if let Ok(mut deployments) = REGISTERED_DEPLOYMENTS.lock() {
+    deployments.remove(deployment_name);
+  }
+}
+
+// Keeps a renamed deployment registered under its new name
+pub(crate) fn rename(old_name: &str, new_name: &str) {
+  if let Ok(mut deployments) = REGISTERED_DEPLOYMENTS.lock() {
+    if deployments.remove(old_name) {
+      deployments.insert(new_name.to_string());
+    }
+  }
+}
+
+// Called from the JavaScript thread while the process is exiting, the event loop won't run any
+// more work so the deployments are removed synchronously on a dedicated runtime
+// This blocks the exit until they are removed, for at most `REMOVE_ON_EXIT_TIMEOUT`
+fn remove_registered_deployments() {
+  let deployments = match REGISTERED_DEPLOYMENTS.lock() {
+    Ok(mut deployments) => std::mem::take(&mut *deployments),
+    Err(_) => return,
+  };
+  if deployments.is_empty() {
+    return;
+  }
+
+  let Ok(runtime) = tokio::runtime::Builder::new_current_thread()
+    .enable_all()
+    .build()
+  else {
+    return;
+  };
+  runtime.block_on(async {
+    let Ok(client) = AtlasLocalClient::connect_with_defaults() else {
+      return;
+    };
+    // Nothing can be reported anymore, removal is best effort
+    let _ = tokio::time::timeout(
+      REMOVE_ON_EXIT_TIMEOUT,
+      join_all(
+        deployments
+          .iter()
+          .map(|deployment_name| client.delete_deployment(deployment_name)),
+      ),
+    )
+    .await;
+  });
+}
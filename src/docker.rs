@@ -0,0 +1,1538 @@
+use std::collections::HashMap;
+use std::time::{Duration, Instant};
+
+use anyhow::{Context, Result};
+use bollard::container::LogOutput;
+use bollard::exec::{CreateExecOptions, StartExecResults};
+use bollard::models::{
+  ContainerCreateBody, ContainerInspectResponse, EndpointSettings, HealthStatusEnum,
+  NetworkConnectRequest, NetworkCreateRequest,
+};
+use bollard::query_parameters::{
+  CreateContainerOptionsBuilder, CreateImageOptionsBuilder, InspectContainerOptions,
+  InspectNetworkOptions, ListContainersOptionsBuilder, ListImagesOptions,
+  RemoveContainerOptionsBuilder, RemoveImageOptionsBuilder, RemoveVolumeOptions,
+  RenameContainerOptionsBuilder, StartContainerOptions, StatsOptionsBuilder, StopContainerOptions,
+  TagImageOptionsBuilder,
+};
+use bollard::Docker;
+use futures_util::TryStreamExt;
+use serde_json::Value;
+use tokio::io::{AsyncRead, AsyncWriteExt};
+
+use crate::errors::DeploymentNameConflictError;
+use crate::models::auth::random_string;
+use crate::models::compose::{is_anonymous_volume, ComposeFile};
+use crate::models::deployment_stats::DeploymentStats;
+use crate::models::expiration::apply_expiration_labels;
+use crate::models::images::{
+  digest_from_repo_digest, pinned_repository, tag_from_reference, unpinned_repository, Image,
+  ResolvedImageTag,
+};
+use crate::models::init_scripts::{
+  InitScriptOutput, InitScriptType, ResolvedInitScript, INIT_SCRIPTS_DIRECTORY,
+};
+use crate::models::list_deployments::{Deployment, State};
+use crate::models::networks::{
+  deployment_networks, networks_to_reconnect, primary_network, DeploymentNetwork, NetworkOptions,
+};
+use crate::models::ports::port_bindings_from_docker;
+use crate::models::replica_set::{
+  apply_oplog_size_label, replica_set_name, supports_change_streams, supports_transactions,
+};
+use crate::models::sample_data::{
+  download_archive, format_sample_datasets, parse_sample_datasets, restore_command,
+  sample_data_archive_path, sample_datasets_file, search_indexes_command, SampleDataProgress,
+  SampleDataStage, SAMPLE_DATASETS,
+};
+use crate::models::secrets::write_secret_command;
+use crate::models::seed::{open_seed_file, seed_command, seed_file_is_json_array, SeedSource};
+use crate::models::server_parameters::apply_parameter_labels;
+use crate::models::update_deployment::{resources_update, ResourceLimits};
+
+// Repository of the images used by atlas-local deployments
+pub(crate) const ATLAS_LOCAL_IMAGE: &str = "mongodb/mongodb-atlas-local";
+
+// Location of the mongod data directory inside the atlas-local image
+pub(crate) const DATA_DIRECTORY: &str = "/data/db";
+
+// Volume of the image next to the dbpath, state kept here survives recreating the container
+pub(crate) const CONFIG_DIRECTORY: &str = "/data/configdb";
+
+// Ports mongod and mongot listen on inside the atlas-local image
+pub(crate) const MONGODB_PORT: u16 = 27017;
+pub(crate) const MONGOT_PORT: u16 = 27027;
+
+// Directory the image entrypoint runs seed scripts from, `local_seed_location` is mounted here
+pub(crate) const INIT_DIRECTORY: &str = "/docker-entrypoint-initdb.d";
+
+// Label atlas-local uses to recognize the containers of its deployments
+pub(crate) const LOCAL_DEPLOYMENT_LABEL: &str = "mongodb-atlas-local";
+pub(crate) const LOCAL_DEPLOYMENT_LABEL_VALUE: &str = "container";
+
+pub(crate) struct ExecOutput {
+  pub exit_code: i64,
+  pub stdout: String,
+  pub stderr: String,
+}
+
+pub(crate) async fn exec(docker: &Docker, container: &str, cmd: Vec<String>) -> Result<ExecOutput> {
+  exec_with_input(docker, container, cmd, None::<&[u8]>).await
+}
+
+// Copies `input` to the stdin of the command and closes it while the output is read, a command
+// writing a lot of output would otherwise block on a full pipe before reading all of its input
+pub(crate) async fn exec_with_input(
+  docker: &Docker,
+  container: &str,
+  cmd: Vec<String>,
+  input: Option<impl AsyncRead + Unpin>,
+) -> Result<ExecOutput> {
+  let created = docker
+    .create_exec(
+      container,
+      CreateExecOptions {
+        cmd: Some(cmd),
+        attach_stdin: Some(input.is_some()),
+        attach_stdout: Some(true),
+        attach_stderr: Some(true),
+        ..Default::default()
+      },
+    )
+    .await
+    .context("create exec")?;
+
+  let mut stdout = String::new();
+  let mut stderr = String::new();
+  let written = if let StartExecResults::Attached {
+    mut output,
+    input: mut stdin,
+  } = docker
+    .start_exec(&created.id, None)
+    .await
+    .context("start exec")?
+  {
+    let write = async {
+      if let Some(mut input) = input {
+        tokio::io::copy(&mut input, &mut stdin)
+          .await
+          .context("write exec input")?;
+        stdin.shutdown().await.context("close exec input")?;
+      }
+      anyhow::Ok(())
+    };
+    let read = async {
+      while let Some(chunk) = output.try_next().await.context("read exec output")? {
+        match chunk {
+          LogOutput::StdOut { message } => stdout.push_str(&String::from_utf8_lossy(&message)),
+          LogOutput::StdErr { message } => stderr.push_str(&String::from_utf8_lossy(&message)),
+          _ => {}
+        }
+      }
+      anyhow::Ok(())
+    };
+    let (written, read) = tokio::join!(write, read);
+    read?;
+    written
+  } else {
+    Ok(())
+  };
+
+  let exit_code = docker
+    .inspect_exec(&created.id)
+    .await
+    .context("inspect exec")?
+    .exit_code
+    .unwrap_or_default();
+  // A command failing before it read all of its input closes stdin, its output tells why
+  if exit_code == 0 {
+    written?;
+  }
+
+  Ok(ExecOutput {
+    exit_code,
+    stdout,
+    stderr,
+  })
+}
+
+// The disk usage is only measured with `disk_usage`
+pub(crate) async fn deployment_stats(
+  docker: &Docker,
+  deployment_name: &str,
+  disk_usage: bool,
+) -> Result<DeploymentStats> {
+  // Streaming is disabled but one-shot is not, this makes docker wait for a second sample
+  // so the previous CPU counters are populated and a CPU percentage can be calculated
+  let options = StatsOptionsBuilder::default()
+    .stream(false)
+    .one_shot(false)
+    .build();
+  let container_stats = Box::pin(docker.stats(deployment_name, Some(options)))
+    .try_next()
+    .await
+    .context("get container stats")?
+    .context("container stats not available")?;
+
+  let mut stats = DeploymentStats::from(container_stats);
+
+  let container = docker
+    .inspect_container(deployment_name, None::<InspectContainerOptions>)
+    .await
+    .context("inspect container")?;
+  stats.oom_killed = container
+    .state
+    .and_then(|state| state.oom_killed)
+    .unwrap_or_default();
+
+  if !disk_usage {
+    return Ok(stats);
+  }
+
+  // Disk usage is best effort, a stopped container can't be inspected with exec
+  stats.data_disk_usage = exec(
+    docker,
+    deployment_name,
+    vec![
+      "du".to_string(),
+      "-sb".to_string(),
+      DATA_DIRECTORY.to_string(),
+    ],
+  )
+  .await
+  .ok()
+  .filter(|output| output.exit_code == 0)
+  .and_then(|output| parse_du_output(&output.stdout));
+
+  Ok(stats)
+}
+
+pub(crate) struct Credentials {
+  pub username: String,
+  pub password: String,
+}
+
+impl Credentials {
+  // Resolves the root credentials of a deployment
+  // The `_FILE` variants are read inside the container
+  pub(crate) async fn for_deployment(
+    docker: &Docker,
+    deployment: &atlas_local::models::Deployment,
+  ) -> Result<Option<Credentials>> {
+    let username = match (
+      &deployment.mongodb_initdb_root_username,
+      &deployment.mongodb_initdb_root_username_file,
+    ) {
+      (Some(username), _) => Some(username.clone()),
+      (None, Some(file)) => Some(read_file(docker, &deployment.container_id, file).await?),
+      (None, None) => None,
+    };
+    let password = match (
+      &deployment.mongodb_initdb_root_password,
+      &deployment.mongodb_initdb_root_password_file,
+    ) {
+      (Some(password), _) => Some(password.clone()),
+      (None, Some(file)) => Some(read_file(docker, &deployment.container_id, file).await?),
+      (None, None) => None,
+    };
+
+    Ok(
+      username
+        .zip(password)
+        .map(|(username, password)| Credentials { username, password }),
+    )
+  }
+}
+
+async fn read_file(docker: &Docker, container: &str, path: &str) -> Result<String> {
+  let output = exec(docker, container, vec!["cat".to_string(), path.to_string()]).await?;
+  if output.exit_code != 0 {
+    anyhow::bail!("read {path}: {}", output.stderr.trim());
+  }
+
+  Ok(output.stdout.trim_end().to_string())
+}
+
+// The password is never passed on the command line, where `ps` and `docker exec inspect` show it
+// mongosh gets it inside the script it runs, the database tools in a config file
+// mongosh can't run a script from stdin, it is written to a private temporary file first
+const MONGOSH_SCRIPT_COMMAND: &str = "umask 077 && script=$(mktemp) && cat > \"$script\" && \
+  mongosh --nodb --quiet --norc --file \"$script\"; status=$?; rm -f \"$script\"; exit $status";
+
+// Script connecting `db` to `database` before running `script`
+fn mongosh_script(credentials: Option<&Credentials>, database: &str, script: &str) -> String {
+  let auth = credentials.map_or(String::new(), |credentials| {
+    format!(
+      "encodeURIComponent({}) + \":\" + encodeURIComponent({}) + \"@\" + ",
+      Value::String(credentials.username.clone()),
+      Value::String(credentials.password.clone())
+    )
+  });
+
+  format!(
+    "db = connect(\"mongodb://\" + {auth}\"127.0.0.1:{MONGODB_PORT}/\" + encodeURIComponent({}) + \
+     \"?directConnection=true&authSource=admin\");\n{script}\n",
+    Value::String(database.to_string())
+  )
+}
+
+async fn run_mongosh(
+  docker: &Docker,
+  container: &str,
+  credentials: Option<&Credentials>,
+  database: &str,
+  script: &str,
+) -> Result<ExecOutput> {
+  let script = mongosh_script(credentials, database, script);
+
+  exec_with_input(
+    docker,
+    container,
+    vec![
+      "sh".to_string(),
+      "-c".to_string(),
+      MONGOSH_SCRIPT_COMMAND.to_string(),
+    ],
+    Some(script.as_bytes()),
+  )
+  .await
+}
+
+// YAML config file of the database tools
+fn tools_config(credentials: &Credentials) -> String {
+  format!(
+    "password: {}\n",
+    Value::String(credentials.password.clone())
+  )
+}
+
+// Runs mongorestore or mongoimport reading `input` from stdin
+async fn run_tool(
+  docker: &Docker,
+  container: &str,
+  credentials: Option<&Credentials>,
+  mut cmd: Vec<String>,
+  input: impl AsyncRead + Unpin,
+) -> Result<ExecOutput> {
+  let Some(credentials) = credentials else {
+    return exec_with_input(docker, container, cmd, Some(input)).await;
+  };
+
+  let config = format!("/tmp/atlas-local-tools-{}.yaml", random_string(16)?);
+  write_secret(docker, container, &config, &tools_config(credentials)).await?;
+  cmd.extend([
+    "--username".to_string(),
+    credentials.username.clone(),
+    "--authenticationDatabase".to_string(),
+    "admin".to_string(),
+    format!("--config={config}"),
+  ]);
+  let output = exec_with_input(docker, container, cmd, Some(input)).await;
+  let _ = exec(
+    docker,
+    container,
+    vec!["rm".to_string(), "-f".to_string(), config],
+  )
+  .await;
+
+  output
+}
+
+// Runs an init script inside the deployment the way the entrypoint of the image runs them, `.js`
+// scripts with mongosh against the initial database and `.sh` scripts with a shell
+// Scripts are copied into the container and run by path, they can be larger than a single command
+// line argument
+pub(crate) async fn run_init_script(
+  docker: &Docker,
+  container: &str,
+  credentials: Option<&Credentials>,
+  database: Option<&str>,
+  script: &ResolvedInitScript,
+) -> Result<InitScriptOutput> {
+  let output = match script.script_type {
+    InitScriptType::Js => {
+      run_mongosh(
+        docker,
+        container,
+        credentials,
+        database.unwrap_or("test"),
+        &script.content,
+      )
+      .await
+    }
+    InitScriptType::Sh => run_shell_script(docker, container, script).await,
+  };
+  let output = output.with_context(|| format!("run init script {}", script.name))?;
+  if output.exit_code != 0 {
+    anyhow::bail!(
+      "init script {} exited with code {}: {}",
+      script.name,
+      output.exit_code,
+      output.stderr.trim()
+    );
+  }
+
+  Ok(InitScriptOutput {
+    name: script.name.clone(),
+    exit_code: output.exit_code,
+    stdout: output.stdout,
+    stderr: output.stderr,
+  })
+}
+
+async fn run_shell_script(
+  docker: &Docker,
+  container: &str,
+  script: &ResolvedInitScript,
+) -> Result<ExecOutput> {
+  let path = script.container_path();
+  let output = exec_with_input(
+    docker,
+    container,
+    vec![
+      "sh".to_string(),
+      "-c".to_string(),
+      format!("mkdir -p {INIT_SCRIPTS_DIRECTORY} && cat > {path}"),
+    ],
+    Some(script.content.as_bytes()),
+  )
+  .await
+  .context("copy script")?;
+  if output.exit_code != 0 {
+    anyhow::bail!("copy script: {}", output.stderr.trim());
+  }
+
+  let output = exec(docker, container, vec!["sh".to_string(), path.clone()]).await;
+  let _ = exec(
+    docker,
+    container,
+    vec!["rm".to_string(), "-f".to_string(), path],
+  )
+  .await;
+
+  output
+}
+
+// Restores the datasets one at a time from a single download of the archive, streamed into
+// mongorestore
+pub(crate) async fn load_sample_data(
+  docker: &Docker,
+  container: &str,
+  credentials: Option<&Credentials>,
+  datasets: &[String],
+  create_search_indexes: bool,
+  on_progress: impl Fn(SampleDataProgress),
+) -> Result<()> {
+  let total = datasets.len() as u32;
+  let progress = |stage, dataset: Option<&String>, completed| {
+    on_progress(SampleDataProgress {
+      stage,
+      dataset: dataset.cloned(),
+      completed,
+      total,
+    })
+  };
+
+  let archive = sample_data_archive_path(container);
+  let loaded = async {
+    progress(SampleDataStage::Downloading, None, 0);
+    download_archive(&archive).await?;
+
+    for (completed, dataset) in (0..).zip(datasets) {
+      progress(SampleDataStage::Restoring, Some(dataset), completed);
+      let file = tokio::fs::File::open(&archive)
+        .await
+        .context("open sample data archive")?;
+      let output = run_tool(
+        docker,
+        container,
+        credentials,
+        restore_command(dataset),
+        file,
+      )
+      .await
+      .with_context(|| format!("restore {dataset}"))?;
+      if output.exit_code != 0 {
+        anyhow::bail!("restore {dataset}: {}", output.stderr.trim());
+      }
+
+      if create_search_indexes {
+        progress(
+          SampleDataStage::CreatingSearchIndexes,
+          Some(dataset),
+          completed,
+        );
+        mongosh_eval(
+          docker,
+          container,
+          credentials,
+          &search_indexes_command(dataset),
+        )
+        .await
+        .with_context(|| format!("create search indexes for {dataset}"))?;
+      }
+
+      record_sample_dataset(docker, container, dataset).await?;
+    }
+
+    progress(SampleDataStage::Done, None, total);
+    Ok(())
+  }
+  .await;
+
+  // The archive is large, it is removed whether loading succeeded or not
+  let _ = tokio::fs::remove_file(&archive).await;
+
+  loaded
+}
+
+// Datasets loaded with `sampleData` or `loadSampleData`, empty when none were
+pub(crate) async fn sample_datasets(docker: &Docker, container: &str) -> Vec<String> {
+  exec(
+    docker,
+    container,
+    vec!["cat".to_string(), sample_datasets_file()],
+  )
+  .await
+  .ok()
+  .filter(|output| output.exit_code == 0)
+  .map(|output| parse_sample_datasets(&output.stdout))
+  .unwrap_or_default()
+}
+
+async fn record_sample_dataset(docker: &Docker, container: &str, dataset: &str) -> Result<()> {
+  let mut datasets = sample_datasets(docker, container).await;
+  datasets.push(dataset.to_string());
+  datasets.sort();
+  datasets.dedup();
+  let content = format_sample_datasets(&datasets);
+
+  let output = exec_with_input(
+    docker,
+    container,
+    vec![
+      "sh".to_string(),
+      "-c".to_string(),
+      format!("cat > {}", sample_datasets_file()),
+    ],
+    Some(content.as_bytes()),
+  )
+  .await
+  .context("record sample datasets")?;
+  if output.exit_code != 0 {
+    anyhow::bail!("record sample datasets: {}", output.stderr.trim());
+  }
+
+  Ok(())
+}
+
+pub(crate) async fn write_secret(
+  docker: &Docker,
+  container: &str,
+  path: &str,
+  secret: &str,
+) -> Result<()> {
+  let output = exec_with_input(
+    docker,
+    container,
+    write_secret_command(path),
+    Some(secret.as_bytes()),
+  )
+  .await
+  .context("write secret")?;
+  if output.exit_code != 0 {
+    anyhow::bail!("write secret: {}", output.stderr.trim());
+  }
+
+  Ok(())
+}
+
+// Imports a seed file into the deployment, the file is streamed to the stdin of the tool
+pub(crate) async fn seed(
+  docker: &Docker,
+  container: &str,
+  credentials: Option<&Credentials>,
+  source: &SeedSource,
+) -> Result<()> {
+  let cmd = seed_command(source, seed_file_is_json_array(source)?);
+  let file = open_seed_file(source).await?;
+
+  let output = run_tool(docker, container, credentials, cmd, file)
+    .await
+    .with_context(|| format!("seed from {}", source.path))?;
+  if output.exit_code != 0 {
+    anyhow::bail!(
+      "seeding from {} exited with code {}: {}",
+      source.path,
+      output.exit_code,
+      output.stderr.trim()
+    );
+  }
+
+  Ok(())
+}
+
+// Evaluates a mongosh expression inside the deployment and parses the (relaxed) EJSON result
+pub(crate) async fn mongosh_eval(
+  docker: &Docker,
+  container: &str,
+  credentials: Option<&Credentials>,
+  expression: &str,
+) -> Result<Value> {
+  let output = run_mongosh(
+    docker,
+    container,
+    credentials,
+    "test",
+    &format!("print(EJSON.stringify({expression}, {{ relaxed: true }}))"),
+  )
+  .await
+  .context("run mongosh")?;
+  if output.exit_code != 0 {
+    anyhow::bail!(
+      "mongosh exited with code {}: {}",
+      output.exit_code,
+      output.stderr.trim()
+    );
+  }
+
+  serde_json::from_str(output.stdout.trim()).context("parse mongosh output")
+}
+
+pub(crate) async fn hello(docker: &Docker, container_id: &str) -> Result<Value> {
+  mongosh_eval(docker, container_id, None, "db.hello()")
+    .await
+    .context("run hello")
+}
+
+// Best effort, mongot only runs in images that bundle search
+pub(crate) async fn mongot_version(docker: &Docker, container: &str) -> Option<String> {
+  exec(
+    docker,
+    container,
+    vec!["mongot".to_string(), "--version".to_string()],
+  )
+  .await
+  .ok()
+  .filter(|output| output.exit_code == 0)
+  .and_then(|output| {
+    output
+      .stdout
+      .lines()
+      .next()
+      .map(|line| line.trim().to_string())
+  })
+  .filter(|version| !version.is_empty())
+}
+
+pub(crate) async fn list_images(docker: &Docker) -> Result<Vec<Image>> {
+  let containers = docker
+    .list_containers(Some(
+      ListContainersOptionsBuilder::default().all(true).build(),
+    ))
+    .await
+    .context("list containers")?;
+
+  let mut used_by: HashMap<String, Vec<String>> = HashMap::new();
+  for container in containers {
+    let (Some(image_id), Some(name)) = (
+      container.image_id,
+      container.names.and_then(|names| names.into_iter().next()),
+    ) else {
+      continue;
+    };
+    used_by
+      .entry(image_id)
+      .or_default()
+      .push(name.trim_start_matches('/').to_string());
+  }
+
+  let images = docker
+    .list_images(None::<ListImagesOptions>)
+    .await
+    .context("list images")?;
+
+  Ok(
+    images
+      .into_iter()
+      .flat_map(|image| Image::from_summary(image, ATLAS_LOCAL_IMAGE, &used_by))
+      .collect(),
+  )
+}
+
+pub(crate) async fn remove_image(docker: &Docker, reference: &str, force: bool) -> Result<()> {
+  docker
+    .remove_image(
+      reference,
+      Some(RemoveImageOptionsBuilder::default().force(force).build()),
+      None,
+    )
+    .await
+    .with_context(|| format!("remove image {reference}"))?;
+
+  Ok(())
+}
+
+pub(crate) async fn pull_image(
+  docker: &Docker,
+  repository: &str,
+  tag_or_digest: &str,
+) -> Result<()> {
+  // Digests are passed as the tag, docker joins them with `@` instead of `:`
+  let options = CreateImageOptionsBuilder::default()
+    .from_image(repository)
+    .tag(tag_or_digest)
+    .build();
+  docker
+    .create_image(Some(options), None, None)
+    .try_collect::<Vec<_>>()
+    .await
+    .with_context(|| format!("pull image {repository}:{tag_or_digest}"))?;
+
+  Ok(())
+}
+
+// Asks the registry for the digest instead of pulling the image
+pub(crate) async fn resolve_image_tag(
+  docker: &Docker,
+  repository: &str,
+  tag: &str,
+) -> Result<ResolvedImageTag> {
+  let reference = format!("{repository}:{tag}");
+  let distribution = docker
+    .inspect_registry_image(&reference, None)
+    .await
+    .with_context(|| format!("inspect registry image {reference}"))?;
+  let digest = distribution
+    .descriptor
+    .digest
+    .with_context(|| format!("image {reference} has no digest"))?;
+
+  let image = docker
+    .inspect_image(&format!("{repository}@{digest}"))
+    .await
+    .ok();
+  let mongodb_version = image
+    .as_ref()
+    .and_then(|image| image.config.as_ref())
+    .and_then(|config| config.labels.as_ref())
+    .and_then(|labels| labels.get("version").cloned())
+    .or_else(|| (tag.split('.').count() == 3).then(|| tag.to_string()));
+
+  Ok(ResolvedImageTag {
+    tag: tag.to_string(),
+    digest,
+    image_id: image.and_then(|image| image.id),
+    mongodb_version,
+  })
+}
+
+// Tags the image with the given digest in a repository of its own and returns that repository,
+// creating a deployment from it (without pulling) runs exactly the pinned image
+pub(crate) async fn pin_image(
+  docker: &Docker,
+  repository: &str,
+  tag: &str,
+  digest: &str,
+) -> Result<String> {
+  pull_image(docker, repository, digest).await?;
+
+  let pinned = pinned_repository(repository, digest);
+  docker
+    .tag_image(
+      &format!("{repository}@{digest}"),
+      Some(
+        TagImageOptionsBuilder::default()
+          .repo(&pinned)
+          .tag(tag)
+          .build(),
+      ),
+    )
+    .await
+    .with_context(|| format!("tag image {repository}@{digest} as {pinned}:{tag}"))?;
+
+  Ok(pinned)
+}
+
+pub(crate) async fn container_labels(
+  docker: &Docker,
+  container: &str,
+) -> Result<HashMap<String, String>> {
+  let inspect = docker
+    .inspect_container(container, None::<InspectContainerOptions>)
+    .await
+    .context("inspect container")?;
+
+  Ok(
+    inspect
+      .config
+      .and_then(|config| config.labels)
+      .unwrap_or_default(),
+  )
+}
+
+// Name of the running container publishing `port` on the host, if any
+pub(crate) async fn container_using_port(docker: &Docker, port: u16) -> Result<Option<String>> {
+  let containers = docker
+    .list_containers(Some(ListContainersOptionsBuilder::default().build()))
+    .await
+    .context("list containers")?;
+
+  Ok(
+    containers
+      .into_iter()
+      .find(|container| {
+        container
+          .ports
+          .iter()
+          .flatten()
+          .any(|published| published.public_port == Some(port))
+      })
+      .and_then(|container| container.names.and_then(|names| names.into_iter().next()))
+      .map(|name| name.trim_start_matches('/').to_string()),
+  )
+}
+
+// Fills in the details atlas-local doesn't report: image tag and digest, cleanup labels, all the
+// published ports and the networks
+pub(crate) async fn with_container_details(
+  docker: &Docker,
+  mut deployment: Deployment,
+) -> Result<Deployment> {
+  let container = docker
+    .inspect_container(&deployment.container_id, None::<InspectContainerOptions>)
+    .await
+    .context("inspect container")?;
+  let config = container.config.unwrap_or_default();
+
+  let labels = config.labels.unwrap_or_default();
+  apply_expiration_labels(&mut deployment, &labels);
+  apply_parameter_labels(&mut deployment, &labels);
+  apply_oplog_size_label(&mut deployment, &labels);
+  deployment.labels = labels;
+
+  let network_settings = container.network_settings.unwrap_or_default();
+  let host_config = container.host_config.unwrap_or_default();
+
+  // The published ports are only known while the container runs, otherwise the requested ones are
+  // reported
+  let ports = network_settings
+    .ports
+    .filter(|ports| !ports.is_empty())
+    .or(host_config.port_bindings)
+    .unwrap_or_default();
+  let port_bindings = port_bindings_from_docker(&ports, MONGODB_PORT);
+  if !port_bindings.is_empty() {
+    deployment.port_bindings = port_bindings;
+  }
+  deployment.mongot_port_bindings = port_bindings_from_docker(&ports, MONGOT_PORT);
+
+  deployment.networks = deployment_networks(
+    &network_settings.networks.unwrap_or_default(),
+    &deployment.container_id,
+  );
+  let primary = primary_network(host_config.network_mode.as_deref());
+  deployment.internal_ip = deployment
+    .networks
+    .iter()
+    .find(|network| network.name == primary)
+    .or(deployment.networks.first())
+    .and_then(|network| network.ip_address.clone());
+
+  // `MONGODB_LOAD_SAMPLE_DATA` loads every dataset
+  if deployment.mongodb_load_sample_data == Some(true) {
+    deployment.sample_datasets = SAMPLE_DATASETS.iter().map(|d| d.to_string()).collect();
+  }
+
+  // The image declares the data directory as a volume, only a named one is reported
+  deployment.data_volume = container
+    .mounts
+    .iter()
+    .flatten()
+    .find(|mount| mount.destination.as_deref() == Some(DATA_DIRECTORY))
+    .and_then(|mount| mount.name.clone())
+    .filter(|volume| !is_anonymous_volume(volume));
+
+  let reference = config.image;
+  deployment.image_tag = reference.as_deref().and_then(tag_from_reference);
+
+  let repository = match (&reference, &deployment.image_tag) {
+    (Some(reference), Some(tag)) => reference
+      .strip_suffix(&format!(":{tag}"))
+      .unwrap_or(reference)
+      .to_string(),
+    (Some(reference), None) => reference.split('@').next().unwrap_or(reference).to_string(),
+    (None, _) => ATLAS_LOCAL_IMAGE.to_string(),
+  };
+  deployment.image = Some(unpinned_repository(&repository).to_string());
+
+  if let Some(image_id) = container.image {
+    // The digest is best effort, the image can be removed while the container exists
+    let image = docker.inspect_image(&image_id).await.unwrap_or_default();
+    // Pinned images run from a repository of their own, their digest is from the original one
+    deployment.image_digest = image
+      .repo_digests
+      .iter()
+      .flatten()
+      .find_map(|repo_digest| digest_from_repo_digest(repo_digest, &repository))
+      .or_else(|| {
+        image.repo_digests.iter().flatten().find_map(|repo_digest| {
+          repo_digest
+            .split_once('@')
+            .map(|(_, digest)| digest.to_string())
+        })
+      });
+  }
+
+  Ok(deployment)
+}
+
+// Details that are read from inside the running container, only fetched for a single deployment
+// since every one of them is an exec
+pub(crate) async fn with_runtime_details(
+  docker: &Docker,
+  mut deployment: Deployment,
+) -> Deployment {
+  if deployment.state != State::Running {
+    return deployment;
+  }
+
+  if let Ok(hello) = hello(docker, &deployment.container_id).await {
+    deployment.replica_set = replica_set_name(&hello).ok();
+    deployment.change_streams_available = Some(supports_change_streams(&hello));
+    deployment.transactions_available = Some(supports_transactions(&hello));
+  }
+  if deployment.mongodb_load_sample_data != Some(true) {
+    deployment.sample_datasets = sample_datasets(docker, &deployment.container_id).await;
+  }
+
+  deployment
+}
+
+// Whether Docker reported that the container (or image, network...) doesn't exist
+pub(crate) fn is_not_found(error: &anyhow::Error) -> bool {
+  error.chain().any(|cause| {
+    matches!(
+      cause.downcast_ref::<bollard::errors::Error>(),
+      Some(bollard::errors::Error::DockerResponseServerError {
+        status_code: 404,
+        ..
+      })
+    )
+  })
+}
+
+pub(crate) async fn export_compose(docker: &Docker, deployment_names: &[String]) -> Result<String> {
+  let mut compose = ComposeFile::default();
+  for deployment_name in deployment_names {
+    let container = docker
+      .inspect_container(deployment_name, None::<InspectContainerOptions>)
+      .await
+      .with_context(|| format!("inspect container {deployment_name}"))?;
+    let image_id = container
+      .image
+      .clone()
+      .with_context(|| format!("container {deployment_name} has no image"))?;
+    let image = docker
+      .inspect_image(&image_id)
+      .await
+      .with_context(|| format!("inspect image {image_id}"))?;
+
+    compose.add_service(container, image);
+  }
+
+  compose.to_yaml()
+}
+
+// Docker can't change the configuration of an existing container
+// This replaces the container with one created from the same configuration after `modify` has been
+// applied to it, volumes are carried over so the data of the deployment is kept
+pub(crate) async fn recreate_container(
+  docker: &Docker,
+  container: &str,
+  modify: impl FnOnce(&mut ContainerCreateBody),
+) -> Result<String> {
+  let inspect = docker
+    .inspect_container(container, None::<InspectContainerOptions>)
+    .await
+    .context("inspect container")?;
+  let id = inspect.id.clone().context("container has no id")?;
+  let name = inspect
+    .name
+    .as_deref()
+    .unwrap_or_default()
+    .trim_start_matches('/')
+    .to_string();
+  let running = inspect
+    .state
+    .as_ref()
+    .and_then(|state| state.running)
+    .unwrap_or_default();
+
+  let networks = networks_to_reconnect(
+    &inspect
+      .network_settings
+      .as_ref()
+      .and_then(|network_settings| network_settings.networks.clone())
+      .unwrap_or_default(),
+    inspect
+      .host_config
+      .as_ref()
+      .and_then(|host_config| host_config.network_mode.as_deref()),
+    &id,
+  );
+
+  let mut body = create_body(inspect);
+  modify(&mut body);
+
+  replace_container(docker, &id, &name, running, body, networks).await?;
+
+  Ok(name)
+}
+
+// Container operations `replace_container` is made of, so that its rollback can be tested without
+// Docker
+pub(crate) trait ContainerOps {
+  async fn create(&self, name: &str, body: ContainerCreateBody) -> Result<String>;
+  async fn connect(&self, container: &str, network: &str, aliases: Vec<String>) -> Result<()>;
+  async fn start(&self, container: &str) -> Result<()>;
+  async fn stop(&self, container: &str) -> Result<()>;
+  async fn rename(&self, container: &str, name: &str) -> Result<()>;
+  // Removes the container even if it is running
+  async fn remove(&self, container: &str) -> Result<()>;
+}
+
+impl ContainerOps for Docker {
+  async fn create(&self, name: &str, body: ContainerCreateBody) -> Result<String> {
+    let created = self
+      .create_container(
+        Some(CreateContainerOptionsBuilder::default().name(name).build()),
+        body,
+      )
+      .await
+      .context("create container")?;
+
+    Ok(created.id)
+  }
+
+  async fn connect(&self, container: &str, network: &str, aliases: Vec<String>) -> Result<()> {
+    connect_network(self, container, network, aliases).await
+  }
+
+  async fn start(&self, container: &str) -> Result<()> {
+    start_container(self, container).await
+  }
+
+  async fn stop(&self, container: &str) -> Result<()> {
+    stop_container(self, container).await
+  }
+
+  async fn rename(&self, container: &str, name: &str) -> Result<()> {
+    rename(self, container, name).await
+  }
+
+  async fn remove(&self, container: &str) -> Result<()> {
+    self
+      .remove_container(
+        container,
+        Some(RemoveContainerOptionsBuilder::default().force(true).build()),
+      )
+      .await
+      .context("remove container")
+  }
+}
+
+// Replaces the container `id` named `name` with one created from `body`
+// The original container is only removed once its replacement runs, when anything fails before
+// that the replacement is removed and the original is put back in place
+async fn replace_container(
+  ops: &impl ContainerOps,
+  id: &str,
+  name: &str,
+  running: bool,
+  body: ContainerCreateBody,
+  networks: Vec<DeploymentNetwork>,
+) -> Result<()> {
+  if running {
+    ops.stop(id).await?;
+  }
+  if let Err(error) = ops.rename(id, &format!("{name}-replaced")).await {
+    return Err(rolled_back(
+      error,
+      restore_container(ops, id, None, running).await,
+    ));
+  }
+
+  let created = match ops.create(name, body).await {
+    Ok(created) => created,
+    Err(error) => {
+      return Err(rolled_back(
+        error,
+        restore_container(ops, id, Some(name), running).await,
+      ))
+    }
+  };
+
+  let configured = async {
+    // A container is created on a single network, the others are connected before starting it
+    for network in networks {
+      ops
+        .connect(&created, &network.name, network.aliases)
+        .await?;
+    }
+    if running {
+      ops.start(&created).await?;
+    }
+
+    anyhow::Ok(())
+  }
+  .await;
+  if let Err(error) = configured {
+    let restored = async {
+      ops.remove(&created).await?;
+      restore_container(ops, id, Some(name), running).await
+    }
+    .await;
+    return Err(rolled_back(error, restored));
+  }
+
+  ops.remove(id).await.context("remove replaced container")
+}
+
+// Gives the original container its name back and restarts it
+async fn restore_container(
+  ops: &impl ContainerOps,
+  id: &str,
+  name: Option<&str>,
+  running: bool,
+) -> Result<()> {
+  if let Some(name) = name {
+    ops.rename(id, name).await?;
+  }
+  if running {
+    ops.start(id).await?;
+  }
+
+  Ok(())
+}
+
+fn rolled_back(error: anyhow::Error, restored: Result<()>) -> anyhow::Error {
+  match restored {
+    Ok(()) => error,
+    Err(restore_error) => error.context(format!(
+      "the original container could not be restored: {restore_error:#}"
+    )),
+  }
+}
+
+pub(crate) async fn start_container(docker: &Docker, container: &str) -> Result<()> {
+  docker
+    .start_container(container, None::<StartContainerOptions>)
+    .await
+    .context("start container")
+}
+
+pub(crate) async fn unpause_container(docker: &Docker, container: &str) -> Result<()> {
+  docker
+    .unpause_container(container)
+    .await
+    .context("unpause container")
+}
+
+pub(crate) async fn stop_container(docker: &Docker, container: &str) -> Result<()> {
+  docker
+    .stop_container(container, None::<StopContainerOptions>)
+    .await
+    .context("stop container")
+}
+
+// Changes the resource limits of a container without restarting it
+pub(crate) async fn update_resources(
+  docker: &Docker,
+  container: &str,
+  resources: &ResourceLimits,
+) -> Result<()> {
+  docker
+    .update_container(container, resources_update(resources))
+    .await
+    .context("update container resources")
+}
+
+// Renames a container in place, its id, configuration and volumes are kept
+// Docker refuses a name another container has with a conflict
+pub(crate) async fn rename_container(docker: &Docker, container: &str, name: &str) -> Result<()> {
+  match docker
+    .rename_container(
+      container,
+      RenameContainerOptionsBuilder::default().name(name).build(),
+    )
+    .await
+  {
+    Err(bollard::errors::Error::DockerResponseServerError {
+      status_code: 409, ..
+    }) => Err(
+      DeploymentNameConflictError {
+        deployment_name: name.to_string(),
+      }
+      .into(),
+    ),
+    renamed => renamed.with_context(|| format!("rename container to {name}")),
+  }
+}
+
+async fn rename(docker: &Docker, container: &str, name: &str) -> Result<()> {
+  docker
+    .rename_container(
+      container,
+      RenameContainerOptionsBuilder::default().name(name).build(),
+    )
+    .await
+    .with_context(|| format!("rename container to {name}"))
+}
+
+fn create_body(container: ContainerInspectResponse) -> ContainerCreateBody {
+  let config = container.config.unwrap_or_default();
+  let mut host_config = container.host_config.unwrap_or_default();
+
+  // Anonymous volumes would be recreated empty, mount them by name instead
+  let mut binds = host_config.binds.take().unwrap_or_default();
+  for mount in container.mounts.unwrap_or_default() {
+    if let (Some("volume"), Some(volume), Some(destination)) =
+      (mount.typ.as_deref(), mount.name, mount.destination)
+    {
+      if !binds
+        .iter()
+        .any(|bind| bind.split(':').nth(1) == Some(destination.as_str()))
+      {
+        binds.push(format!("{volume}:{destination}"));
+      }
+    }
+  }
+  host_config.binds = Some(binds);
+
+  ContainerCreateBody {
+    hostname: config.hostname,
+    domainname: config.domainname,
+    user: config.user,
+    exposed_ports: config.exposed_ports,
+    env: config.env,
+    cmd: config.cmd,
+    healthcheck: config.healthcheck,
+    entrypoint: config.entrypoint,
+    image: config.image,
+    labels: config.labels,
+    working_dir: config.working_dir,
+    stop_signal: config.stop_signal,
+    stop_timeout: config.stop_timeout,
+    host_config: Some(host_config),
+    ..Default::default()
+  }
+}
+
+// Mounts `volume` as the data directory in place of the volume the container had, returns the
+// replaced volume if it was an anonymous one
+pub(crate) fn set_data_volume(body: &mut ContainerCreateBody, volume: &str) -> Option<String> {
+  let binds = body
+    .host_config
+    .get_or_insert_with(Default::default)
+    .binds
+    .get_or_insert_with(Vec::new);
+  let mut replaced = None;
+  binds.retain(|bind| {
+    let mut parts = bind.split(':');
+    let (source, destination) = (parts.next(), parts.next());
+    if destination != Some(DATA_DIRECTORY) {
+      return true;
+    }
+    replaced = source
+      .filter(|source| is_anonymous_volume(source))
+      .map(str::to_string);
+    false
+  });
+  binds.push(format!("{volume}:{DATA_DIRECTORY}"));
+
+  replaced
+}
+
+pub(crate) async fn remove_volume(docker: &Docker, volume: &str) -> Result<()> {
+  docker
+    .remove_volume(volume, None::<RemoveVolumeOptions>)
+    .await
+    .with_context(|| format!("remove volume {volume}"))
+}
+
+pub(crate) async fn connect_network(
+  docker: &Docker,
+  container: &str,
+  network: &str,
+  aliases: Vec<String>,
+) -> Result<()> {
+  docker
+    .connect_network(
+      network,
+      NetworkConnectRequest {
+        container: container.to_string(),
+        endpoint_config: Some(EndpointSettings {
+          aliases: Some(aliases),
+          ..Default::default()
+        }),
+      },
+    )
+    .await
+    .with_context(|| format!("connect to network {network}"))
+}
+
+// Connects a deployment to a network, creating the network first if asked to
+pub(crate) async fn join_network(
+  docker: &Docker,
+  container: &str,
+  options: NetworkOptions,
+) -> Result<()> {
+  let exists = docker
+    .inspect_network(&options.name, None::<InspectNetworkOptions>)
+    .await
+    .is_ok();
+  if !exists && options.create.unwrap_or_default() {
+    docker
+      .create_network(NetworkCreateRequest {
+        name: options.name.clone(),
+        driver: Some("bridge".to_string()),
+        ..Default::default()
+      })
+      .await
+      .with_context(|| format!("create network {}", options.name))?;
+  }
+
+  connect_network(
+    docker,
+    container,
+    &options.name,
+    options.aliases.unwrap_or_default(),
+  )
+  .await
+}
+
+pub(crate) async fn wait_until_healthy(
+  docker: &Docker,
+  container: &str,
+  timeout: Duration,
+) -> Result<()> {
+  let deadline = Instant::now() + timeout;
+  loop {
+    let state = docker
+      .inspect_container(container, None::<InspectContainerOptions>)
+      .await
+      .context("inspect container")?
+      .state
+      .unwrap_or_default();
+    if !state.running.unwrap_or_default() {
+      anyhow::bail!("container {container} is not running");
+    }
+    match state.health.and_then(|health| health.status) {
+      Some(HealthStatusEnum::HEALTHY) => return Ok(()),
+      Some(HealthStatusEnum::UNHEALTHY) => anyhow::bail!("container {container} is unhealthy"),
+      _ => {}
+    }
+    if Instant::now() >= deadline {
+      anyhow::bail!("container {container} did not get healthy within {timeout:?}");
+    }
+    tokio::time::sleep(Duration::from_secs(1)).await;
+  }
+}
+
+// Gives a container created outside of the library the label atlas-local uses to find deployments
+pub(crate) async fn adopt_container(docker: &Docker, container_id: &str) -> Result<String> {
+  let container = docker
+    .inspect_container(container_id, None::<InspectContainerOptions>)
+    .await
+    .context("inspect container")?;
+  let config = container.config.unwrap_or_default();
+
+  // Docker removes the container along with its anonymous volumes once it is stopped to be
+  // recreated
+  if container
+    .host_config
+    .and_then(|host_config| host_config.auto_remove)
+    .unwrap_or_default()
+  {
+    anyhow::bail!(
+      "container {container_id} is removed by Docker when it stops (--rm), it can't be adopted"
+    );
+  }
+  let image = config.image.unwrap_or_default();
+  if !image.contains("mongodb-atlas-local") {
+    anyhow::bail!("container {container_id} does not run an atlas-local image ({image})");
+  }
+  if config
+    .labels
+    .unwrap_or_default()
+    .get(LOCAL_DEPLOYMENT_LABEL)
+    .is_some_and(|value| value == LOCAL_DEPLOYMENT_LABEL_VALUE)
+  {
+    anyhow::bail!("container {container_id} is already an atlas-local deployment");
+  }
+
+  recreate_container(docker, container_id, |body| {
+    body.labels.get_or_insert_with(HashMap::new).insert(
+      LOCAL_DEPLOYMENT_LABEL.to_string(),
+      LOCAL_DEPLOYMENT_LABEL_VALUE.to_string(),
+    );
+  })
+  .await
+}
+
+fn parse_du_output(output: &str) -> Option<i64> {
+  output.split_whitespace().next()?.parse().ok()
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  // Records the operations and fails connecting to networks
+  #[derive(Default)]
+  struct FailingConnectOps {
+    calls: std::sync::Mutex<Vec<String>>,
+  }
+
+  impl FailingConnectOps {
+    fn record(&self, call: String) {
+      self.calls.lock().unwrap().push(call);
+    }
+  }
+
+  impl ContainerOps for FailingConnectOps {
+    async fn create(&self, name: &str, _body: ContainerCreateBody) -> Result<String> {
+      self.record(format!("create {name}"));
+      Ok("new_id".to_string())
+    }
+
+    async fn connect(&self, container: &str, network: &str, _aliases: Vec<String>) -> Result<()> {
+      self.record(format!("connect {container} {network}"));
+      anyhow::bail!("network {network} not found")
+    }
+
+    async fn start(&self, container: &str) -> Result<()> {
+      self.record(format!("start {container}"));
+      Ok(())
+    }
+
+    async fn stop(&self, container: &str) -> Result<()> {
+      self.record(format!("stop {container}"));
+      Ok(())
+    }
+
+    async fn rename(&self, container: &str, name: &str) -> Result<()> {
+      self.record(format!("rename {container} {name}"));
+      Ok(())
+    }
+
+    async fn remove(&self, container: &str) -> Result<()> {
+      self.record(format!("remove {container}"));
+      Ok(())
+    }
+  }
+
+  #[test]
+  fn test_replace_container_rolls_back() {
+    let ops = FailingConnectOps::default();
+    let networks = vec![DeploymentNetwork {
+      name: "test_network".to_string(),
+      aliases: vec![],
+      ip_address: None,
+    }];
+
+    let result = tokio::runtime::Builder::new_current_thread()
+      .build()
+      .unwrap()
+      .block_on(replace_container(
+        &ops,
+        "old_id",
+        "test_deployment",
+        true,
+        ContainerCreateBody::default(),
+        networks,
+      ));
+
+    assert_eq!(
+      result.unwrap_err().to_string(),
+      "network test_network not found"
+    );
+    assert_eq!(
+      *ops.calls.lock().unwrap(),
+      vec![
+        "stop old_id",
+        "rename old_id test_deployment-replaced",
+        "create test_deployment",
+        "connect new_id test_network",
+        "remove new_id",
+        "rename old_id test_deployment",
+        "start old_id",
+      ]
+    );
+  }
+
+  #[test]
+  fn test_set_data_volume() {
+    let anonymous = "a".repeat(64);
+    let mut body = ContainerCreateBody {
+      host_config: Some(bollard::models::HostConfig {
+        binds: Some(vec![
+          "/host/seed-data:/docker-entrypoint-initdb.d:rw".to_string(),
+          format!("{anonymous}:/data/db"),
+          format!("{}:/data/configdb", "b".repeat(64)),
+        ]),
+        ..Default::default()
+      }),
+      ..Default::default()
+    };
+
+    assert_eq!(
+      set_data_volume(&mut body, "test_deployment_data"),
+      Some(anonymous)
+    );
+    assert_eq!(
+      body.host_config.unwrap().binds.unwrap(),
+      vec![
+        "/host/seed-data:/docker-entrypoint-initdb.d:rw".to_string(),
+        format!("{}:/data/configdb", "b".repeat(64)),
+        "test_deployment_data:/data/db".to_string(),
+      ]
+    );
+  }
+
+  #[test]
+  fn test_parse_du_output() {
+    assert_eq!(parse_du_output("123456\t/data/db\n"), Some(123456));
+    assert_eq!(parse_du_output(""), None);
+    assert_eq!(parse_du_output("du: cannot access"), None);
+  }
+
+  #[test]
+  fn test_mongosh_script() {
+    let credentials = Credentials {
+      username: "admin".to_string(),
+      password: "p@ss\"word".to_string(),
+    };
+
+    assert_eq!(
+      mongosh_script(Some(&credentials), "app", "db.users.drop()"),
+      "db = connect(\"mongodb://\" + encodeURIComponent(\"admin\") + \":\" + \
+       encodeURIComponent(\"p@ss\\\"word\") + \"@\" + \"127.0.0.1:27017/\" + \
+       encodeURIComponent(\"app\") + \"?directConnection=true&authSource=admin\");\n\
+       db.users.drop()\n"
+    );
+    assert!(mongosh_script(None, "test", "db.hello()")
+      .starts_with("db = connect(\"mongodb://\" + \"127.0.0.1:27017/\" + "));
+  }
+
+  #[test]
+  fn test_tools_config() {
+    let credentials = Credentials {
+      username: "admin".to_string(),
+      password: "p@ss\"word".to_string(),
+    };
+
+    assert_eq!(tools_config(&credentials), "password: \"p@ss\\\"word\"\n");
+  }
+}
@@ -0,0 +1,49 @@
+use std::sync::atomic::{AtomicU32, Ordering};
+use std::time::{SystemTime, UNIX_EPOCH};
+
+use napi::bindgen_prelude::{Function, JsObjectValue, Object};
+use napi::Env;
+
+static COUNTER: AtomicU32 = AtomicU32::new(0);
+
+// Makes `await using deployment = await client.createEphemeralDeployment()` dispose the deployment
+const ASYNC_DISPOSE_SCRIPT: &str = r#"
+(function (EphemeralDeployment) {
+  const asyncDispose = Symbol.asyncDispose ?? Symbol.for('nodejs.asyncDispose')
+  EphemeralDeployment.prototype[asyncDispose] = function () {
+    return this.dispose()
+  }
+})
+"#;
+
+pub(crate) fn install_async_dispose(exports: &Object, env: &Env) -> napi::Result<()> {
+  let ephemeral_deployment: Function<(), ()> = exports.get_named_property("EphemeralDeployment")?;
+  let install: Function<Function<(), ()>, ()> = env.run_script(ASYNC_DISPOSE_SCRIPT)?;
+
+  install.call(ephemeral_deployment)
+}
+
+// Unique per process (pid) and per call, e.g. `ephemeral-4242-18c4e1b2a3f-0`
+pub(crate) fn unique_name(prefix: &str) -> String {
+  let timestamp = SystemTime::now()
+    .duration_since(UNIX_EPOCH)
+    .map(|duration| duration.as_millis())
+    .unwrap_or_default();
+  let counter = COUNTER.fetch_add(1, Ordering::SeqCst);
+
+  format!("{prefix}-{}-{timestamp:x}-{counter}", std::process::id())
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  #[test]
+  fn test_unique_name() {
+    let first = unique_name("ephemeral");
+    let second = unique_name("ephemeral");
+
+    assert!(first.starts_with(&format!("ephemeral-{}-", std::process::id())));
+    assert_ne!(first, second);
+  }
+}
@@ -0,0 +1,199 @@
+use std::fmt;
+use std::future::Future;
+
+use napi::bindgen_prelude::{FromNapiValue, JsObjectValue, Object, PromiseRaw, ToNapiValue};
+use napi::{Env, JsError};
+
+use crate::models::ports::PortProcess;
+
+// Errors JavaScript can tell apart, they are thrown with one of these as their `code`
+// errors.js exports a class per code that `instanceof` checks against
+pub(crate) const PORT_IN_USE: &str = "ERR_PORT_IN_USE";
+pub(crate) const DEPLOYMENT_NAME_CONFLICT: &str = "ERR_DEPLOYMENT_NAME_CONFLICT";
+pub(crate) const ROOT_PASSWORD_NOT_STORED: &str = "ERR_ROOT_PASSWORD_NOT_STORED";
+
+// Runs `future` as the promise of a method that can fail with a typed error
+// The error of an async method always has one of napi's codes, this rejects with a JavaScript
+// error that has the code of the typed error found anywhere in the chain of contexts
+pub(crate) fn spawn_with_code<T>(
+  env: &Env,
+  future: impl Future<Output = anyhow::Result<T>> + Send + 'static,
+) -> napi::Result<PromiseRaw<'_, T>>
+where
+  T: ToNapiValue + Send + 'static,
+{
+  env.spawn_future_with_callback(async move { Ok(future.await) }, |env, result| {
+    result.map_err(|error| to_js_error(env, error))
+  })
+}
+
+fn to_js_error(env: &Env, error: anyhow::Error) -> napi::Error {
+  let Some(code) = error_code(&error) else {
+    return error.into();
+  };
+  let password = error.chain().find_map(|cause| {
+    cause
+      .downcast_ref::<RootPasswordNotStoredError>()
+      .map(|error| error.password.clone())
+  });
+
+  // Same message and causes as napi's conversion
+  let napi::Error { reason, cause, .. } = error.into();
+  let mut error = napi::Error::new(code, reason);
+  error.cause = cause;
+  let error = JsError::from(error).into_unknown(*env);
+  if let Some(password) = password {
+    // Best effort, the error is still thrown when the property can't be set
+    if let Ok(mut object) = Object::from_unknown(error) {
+      let _ = object.set_named_property("password", password);
+    }
+  }
+
+  error.into()
+}
+
+fn error_code(error: &anyhow::Error) -> Option<&'static str> {
+  error.chain().find_map(|cause| {
+    if cause.is::<PortInUseError>() {
+      Some(PORT_IN_USE)
+    } else if cause.is::<DeploymentNameConflictError>() {
+      Some(DEPLOYMENT_NAME_CONFLICT)
+    } else if cause.is::<RootPasswordNotStoredError>() {
+      Some(ROOT_PASSWORD_NOT_STORED)
+    } else {
+      None
+    }
+  })
+}
+
+#[derive(PartialEq, Debug)]
+pub(crate) struct PortInUseError {
+  pub ip: String,
+  pub port: u16,
+  pub container: Option<String>,
+  pub process: Option<PortProcess>,
+}
+
+impl fmt::Display for PortInUseError {
+  fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+    write!(f, "port {} on {} is already in use", self.port, self.ip)?;
+    // A published port is held by Docker's proxy, the container is the more useful name then
+    match (&self.container, &self.process) {
+      (Some(container), Some(process)) => write!(
+        f,
+        " by container {container} ({}, pid {})",
+        process.name, process.pid
+      ),
+      (Some(container), None) => write!(f, " by container {container}"),
+      (None, Some(process)) => write!(f, " by {} (pid {})", process.name, process.pid),
+      (None, None) => Ok(()),
+    }
+  }
+}
+
+impl std::error::Error for PortInUseError {}
+
+#[derive(PartialEq, Debug)]
+pub(crate) struct DeploymentNameConflictError {
+  pub deployment_name: String,
+}
+
+impl fmt::Display for DeploymentNameConflictError {
+  fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+    write!(
+      f,
+      "name {} is already in use by another container",
+      self.deployment_name
+    )
+  }
+}
+
+impl std::error::Error for DeploymentNameConflictError {}
+
+// mongod has the new password but the deployment couldn't be updated with it, the password is only
+// known here so it is thrown in the `password` property of the error, never in its message
+pub(crate) struct RootPasswordNotStoredError {
+  pub password: String,
+  pub cause: anyhow::Error,
+}
+
+impl fmt::Debug for RootPasswordNotStoredError {
+  fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+    f.debug_struct("RootPasswordNotStoredError")
+      .field("cause", &self.cause)
+      .finish_non_exhaustive()
+  }
+}
+
+impl fmt::Display for RootPasswordNotStoredError {
+  fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+    write!(f, "the password was changed but not stored")
+  }
+}
+
+impl std::error::Error for RootPasswordNotStoredError {
+  fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+    Some(self.cause.as_ref())
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use anyhow::Context;
+
+  use super::*;
+
+  #[test]
+  fn test_port_in_use_error_message() {
+    let error = PortInUseError {
+      ip: "127.0.0.1".to_string(),
+      port: 27017,
+      container: Some("test_deployment".to_string()),
+      process: None,
+    };
+    assert_eq!(
+      error.to_string(),
+      "port 27017 on 127.0.0.1 is already in use by container test_deployment"
+    );
+
+    let error = PortInUseError {
+      ip: "127.0.0.1".to_string(),
+      port: 27017,
+      container: None,
+      process: Some(PortProcess {
+        pid: 4242,
+        name: "mongod".to_string(),
+      }),
+    };
+    assert_eq!(
+      error.to_string(),
+      "port 27017 on 127.0.0.1 is already in use by mongod (pid 4242)"
+    );
+  }
+
+  #[test]
+  fn test_error_code() {
+    let error = Err::<(), _>(anyhow::Error::new(DeploymentNameConflictError {
+      deployment_name: "taken".to_string(),
+    }))
+    .context("rename deployment")
+    .unwrap_err();
+
+    assert_eq!(error_code(&error), Some(DEPLOYMENT_NAME_CONFLICT));
+    assert_eq!(error_code(&anyhow::anyhow!("not found")), None);
+  }
+
+  #[test]
+  fn test_root_password_not_stored_error() {
+    let error = anyhow::Error::new(RootPasswordNotStoredError {
+      password: "s3cret".to_string(),
+      cause: anyhow::anyhow!("container not found"),
+    })
+    .context("rotate root password");
+
+    assert_eq!(error_code(&error), Some(ROOT_PASSWORD_NOT_STORED));
+    let message = format!("{error:#} {error:?}");
+    assert!(message.contains("the password was changed but not stored: container not found"));
+    assert!(!message.contains("s3cret"));
+  }
+}
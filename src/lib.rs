@@ -1,51 +1,643 @@
 #![deny(clippy::all)]
 
+use std::collections::HashMap;
+use std::future::Future;
+use std::net::IpAddr;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant, SystemTime, UNIX_EPOCH};
+
 use anyhow::{Context, Result};
+use atlas_local::models::ImageTag;
 use atlas_local::Client as AtlasLocalClient;
+use bollard::Docker;
+use futures_util::future::join_all;
+use futures_util::{stream, StreamExt};
+use napi::bindgen_prelude::{FnArgs, Object, Promise, PromiseRaw};
+use napi::threadsafe_function::{ThreadsafeFunction, ThreadsafeFunctionCallMode};
+use napi::{Either, Env, Status};
 use napi_derive::napi;
+use serde_json::{Map, Value};
+use tokio::sync::Notify;
 
-use crate::models::list_deployments::Deployment;
+use crate::errors::{spawn_with_code, PortInUseError, RootPasswordNotStoredError};
+use crate::models::auth::{
+  generate_credentials, generate_password, remove_env, set_env, update_password_command,
+  AuthOptions, RootPasswordRotation, ROOT_PASSWORD_ENV,
+};
+use crate::models::connection_string::{
+  connection_host, network_host, with_host, ConnectFrom, ConnectionStringOptions,
+};
+use crate::models::create_deployment::CreateDeploymentOptions;
+use crate::models::deployment_filter::{
+  check_bulk_filter, matches_filter, start_action, BulkOperationResult, DeploymentFilter,
+  StartAction, BULK_CONCURRENCY,
+};
+use crate::models::deployment_spec::{
+  plan_deployments, DeploymentPlan, DeploymentSpec, PlannedActionType, SPEC_LABEL, SPEC_LABEL_VALUE,
+};
+use crate::models::deployment_stats::{DeploymentStats, DISK_USAGE_INTERVAL};
+use crate::models::expiration::{
+  expiration_labels, reap_reason, Owner, ReapOptions, ReapedDeployment,
+};
+use crate::models::image_tag::ParsedImageTag;
+use crate::models::images::{
+  image_reference, prunable_images, Image, PruneImagesOptions, PruneImagesResult,
+  RemoveImageOptions, ResolvedImageTag,
+};
+use crate::models::import_deployment::ImportedDeployment;
+use crate::models::init_scripts::{resolve_init_script, InitScriptOutput, ResolvedInitScript};
+use crate::models::list_deployments::{BindingType, Deployment, MongoDBPortBinding, State};
+use crate::models::ports::{
+  add_port_bindings, first_free_port, host_ip, is_port_free, pin_port, process_using_port,
+};
+use crate::models::replica_set::{
+  oplog_size_labels, resize_oplog_command, validate_oplog_size, with_query_option,
+};
+use crate::models::sample_data::{resolve_datasets, SampleDataProgress};
+use crate::models::secrets::{
+  add_secrets_tmpfs, read_secret_env, read_secret_file, secret_file, secret_source,
+  secret_source_from_labels, secret_source_labels, SecretSource, ROOT_PASSWORD_FILE_ENV,
+  ROOT_PASSWORD_SECRET,
+};
+use crate::models::seed::{validate_seed_source, SeedSource};
+use crate::models::server_parameters::{
+  config_sections, merged_mongod_config, mongod_config_file, parameter_labels, parse_mongod_config,
+  server_parameters, set_parameter_args, set_parameter_command, validate_runtime_parameters,
+};
+use crate::models::server_status::{BuildInfo, ServerStatus};
+use crate::models::update_deployment::{
+  apply_patch, needs_recreate, validate_patch, DeploymentPatch, UpdateDeploymentResult,
+};
+use crate::models::users::{
+  check_command_response, create_role_command, create_user_command, drop_user_command,
+  parse_users_info, update_user_command, users_info_command, CreateRoleOptions, CreateUserOptions,
+  DatabaseUser, ListUsersOptions, UpdateUserOptions,
+};
 
+mod cleanup;
+mod docker;
+mod ephemeral;
+mod errors;
 pub mod models;
 
+// Seconds to wait for a recreated deployment to get healthy when no timeout is given
+const DEFAULT_WAIT_UNTIL_HEALTHY_TIMEOUT: u32 = 120;
+
+// Resolves a secret by its name, set with `setSecretProvider`
+type SecretProvider = ThreadsafeFunction<String, Promise<String>, String, Status, false, true>;
+
+// Called with an ephemeral deployment and its connection string, see `withEphemeralDeployment`
+type EphemeralCallback = ThreadsafeFunction<
+  FnArgs<(Deployment, String)>,
+  Promise<()>,
+  FnArgs<(Deployment, String)>,
+  Status,
+  false,
+>;
+
+#[napi(module_exports)]
+pub fn init(exports: Object, env: Env) -> napi::Result<()> {
+  ephemeral::install_async_dispose(&exports, &env)
+}
+
+// Cloned into the promises of the methods that can fail with a typed error
 #[napi]
+#[derive(Clone)]
 pub struct Client {
-  client: AtlasLocalClient,
+  // Shared with the ephemeral deployments, which delete themselves
+  client: Arc<AtlasLocalClient>,
+  docker: Docker,
+  secret_provider: Arc<Mutex<Option<Arc<SecretProvider>>>>,
 }
 
 #[napi]
 impl Client {
   #[napi(factory)]
-  pub fn connect() -> Result<Client> {
+  pub fn connect(env: Env) -> Result<Client> {
     let atlas_local_client =
       AtlasLocalClient::connect_with_defaults().context("connect to docker")?;
+    let docker = Docker::connect_with_defaults().context("connect to docker")?;
+
+    cleanup::prepare_exit_hook(&env)
+      .map_err(|error| anyhow::Error::msg(error.to_string()))
+      .context("prepare exit hook")?;
 
     Ok(Client {
-      client: atlas_local_client,
+      client: Arc::new(atlas_local_client),
+      docker,
+      secret_provider: Arc::new(Mutex::new(None)),
     })
   }
 
   #[napi]
-  pub async fn create_deployment(
+  pub fn create_deployment<'env>(
+    &self,
+    env: &'env Env,
+    create_deploment_options: CreateDeploymentOptions,
+    on_sample_data_progress: Option<
+      ThreadsafeFunction<SampleDataProgress, (), SampleDataProgress, Status, false>,
+    >,
+  ) -> napi::Result<PromiseRaw<'env, Deployment>> {
+    let client = self.clone();
+    spawn_with_code(env, async move {
+      client
+        .create(
+          create_deploment_options,
+          on_sample_data_progress,
+          HashMap::new(),
+        )
+        .await
+    })
+  }
+
+  // `extra_labels` are set on the container along with the labels of the options
+  async fn create(
     &self,
-    create_deploment_options: crate::models::create_deployment::CreateDeploymentOptions,
+    create_deploment_options: CreateDeploymentOptions,
+    on_sample_data_progress: Option<
+      ThreadsafeFunction<SampleDataProgress, (), SampleDataProgress, Status, false>,
+    >,
+    extra_labels: HashMap<String, String>,
   ) -> Result<Deployment> {
-    self
+    let mut create_deploment_options = create_deploment_options;
+    if let Some(digest) = create_deploment_options.image_digest.take() {
+      let repository = create_deploment_options
+        .image
+        .clone()
+        .unwrap_or_else(|| docker::ATLAS_LOCAL_IMAGE.to_string());
+      let tag = create_deploment_options
+        .image_tag
+        .get_or_insert_with(|| "latest".to_string())
+        .clone();
+
+      let pinned = docker::pin_image(&self.docker, &repository, &tag, &digest)
+        .await
+        .context("pin image digest")?;
+      create_deploment_options.image = Some(pinned);
+      create_deploment_options.skip_pull_image = Some(true);
+    }
+
+    self.select_port(&mut create_deploment_options).await?;
+
+    let generated_credentials = match create_deploment_options.auth.take() {
+      Some(AuthOptions {
+        generate: Some(true),
+      }) => {
+        let options = &mut create_deploment_options;
+        if options.mongodb_initdb_root_username.is_some()
+          || options.mongodb_initdb_root_username_file.is_some()
+          || options.mongodb_initdb_root_password.is_some()
+          || options.mongodb_initdb_root_password_file.is_some()
+        {
+          anyhow::bail!("set either generated credentials or a root username and password");
+        }
+        let credentials = generate_credentials()?;
+        options.mongodb_initdb_root_username = Some(credentials.username.clone());
+        options.mongodb_initdb_root_password = Some(credentials.password.clone());
+        Some(credentials)
+      }
+      _ => None,
+    };
+
+    let options = &mut create_deploment_options;
+    let root_password = match secret_source(
+      "root password",
+      options.root_password_from_file.take(),
+      options.root_password_from_env.take(),
+      options.root_password_secret.take(),
+    )? {
+      Some(source) => {
+        if options.mongodb_initdb_root_password.is_some()
+          || options.mongodb_initdb_root_password_file.is_some()
+        {
+          anyhow::bail!("set either a root password or where to read it from");
+        }
+        if options.mongodb_initdb_root_username.is_none()
+          && options.mongodb_initdb_root_username_file.is_none()
+        {
+          anyhow::bail!("reading the root password needs a root username");
+        }
+        let password = self.read_secret(&source).await?;
+        // The root user is created with a throwaway password on the first start, it is replaced
+        // once the deployment is healthy so the secret never is in the environment of a container
+        options.mongodb_initdb_root_password = Some(generate_password()?);
+        Some((source, password))
+      }
+      None => None,
+    };
+    let (root_password_source, root_password) = root_password.unzip();
+
+    let mut labels = expiration_labels(&create_deploment_options, &Owner::current(), unix_now()?);
+    if let Some(source) = &root_password_source {
+      labels.extend(secret_source_labels(source)?);
+    }
+    let auto_remove_on_exit = create_deploment_options
+      .auto_remove_on_exit
+      .unwrap_or_default();
+    // Registered before creating so the deployment is removed even if the process exits while
+    // it is being created
+    if let (true, Some(name)) = (auto_remove_on_exit, &create_deploment_options.name) {
+      cleanup::register(name);
+    }
+    let additional_port_bindings = create_deploment_options
+      .additional_port_bindings
+      .take()
+      .unwrap_or_default();
+    let mongot_port_bindings: Vec<_> = create_deploment_options
+      .mongot_port_binding
+      .take()
+      .into_iter()
+      .collect();
+    let network = create_deploment_options.network.take();
+    let data_volume = create_deploment_options.data_volume.take();
+    let oplog_size_mb = create_deploment_options.oplog_size_mb.take();
+    if let Some(oplog_size_mb) = oplog_size_mb {
+      validate_oplog_size(oplog_size_mb)?;
+      labels.extend(oplog_size_labels(oplog_size_mb));
+    }
+    let mongod_config = create_deploment_options
+      .mongod_config
+      .take()
+      .map(parse_mongod_config)
+      .transpose()?;
+    let set_parameters = create_deploment_options.set_parameters.take();
+    // Script files are read before creating so a missing file doesn't leave a deployment behind
+    let init_scripts = create_deploment_options
+      .init_scripts
+      .take()
+      .unwrap_or_default()
+      .into_iter()
+      .map(resolve_init_script)
+      .collect::<Result<Vec<_>>>()?;
+    let sample_data = create_deploment_options.sample_data.take();
+    if sample_data.is_some() && create_deploment_options.load_sample_data == Some(true) {
+      anyhow::bail!("set either loadSampleData or sampleData, not both");
+    }
+    let sample_data = sample_data
+      .map(|sample_data| {
+        resolve_datasets(sample_data.datasets.as_deref()).map(|datasets| {
+          (
+            datasets,
+            sample_data.create_search_indexes.unwrap_or_default(),
+          )
+        })
+      })
+      .transpose()?;
+    let seed = create_deploment_options.seed.take().unwrap_or_default();
+    seed.iter().try_for_each(validate_seed_source)?;
+    let server_parameters = server_parameters(mongod_config.as_ref(), set_parameters.as_ref())?;
+    let config_sections = config_sections(mongod_config.as_ref());
+    labels.extend(parameter_labels(
+      mongod_config.as_ref(),
+      set_parameters.as_ref(),
+    )?);
+    labels.extend(extra_labels);
+    let wait_until_healthy = create_deploment_options
+      .wait_until_healthy
+      .unwrap_or_default();
+    let wait_until_healthy_timeout = Duration::from_secs(
+      create_deploment_options
+        .wait_until_healthy_timeout
+        .unwrap_or(DEFAULT_WAIT_UNTIL_HEALTHY_TIMEOUT) as u64,
+    );
+
+    let deployment = self
       .client
       .create_deployment(create_deploment_options.try_into()?)
       .await
-      .map(|d| d.into())
-      .context("create deployment")
+      .context("create deployment")?;
+    let deployment_name = deployment
+      .name
+      .clone()
+      .unwrap_or_else(|| deployment.container_id.clone());
+    if auto_remove_on_exit {
+      cleanup::register(&deployment_name);
+    }
+
+    // atlas-local can only set a single port binding and no labels on the containers it creates,
+    // the container is recreated with the rest of the configuration which restarts mongod
+    // With a data volume the first start runs again in it, unless the volume already has data
+    if !labels.is_empty()
+      || data_volume.is_some()
+      || !additional_port_bindings.is_empty()
+      || !mongot_port_bindings.is_empty()
+      || !server_parameters.is_empty()
+      || config_sections.is_some()
+      || root_password.is_some()
+    {
+      let configured = async {
+        // Stopping the container before the entrypoint is done with the first start would leave
+        // the data volume half initialized
+        docker::wait_until_healthy(&self.docker, &deployment_name, wait_until_healthy_timeout)
+          .await?;
+        // Read before the root password changes, the credentials of the deployment still work
+        if let Some(sections) = &config_sections {
+          let credentials = docker::Credentials::for_deployment(&self.docker, &deployment).await?;
+          let command_line_options = docker::mongosh_eval(
+            &self.docker,
+            &deployment.container_id,
+            credentials.as_ref(),
+            "db.adminCommand({ getCmdLineOpts: 1 })",
+          )
+          .await
+          .context("get mongod options")?;
+          docker::write_secret(
+            &self.docker,
+            &deployment.container_id,
+            &mongod_config_file(),
+            &merged_mongod_config(&command_line_options, sections)?,
+          )
+          .await
+          .context("write mongod configuration")?;
+        }
+        let root_password_file = secret_file(ROOT_PASSWORD_SECRET);
+        if let Some(root_password) = &root_password {
+          let credentials = docker::Credentials::for_deployment(&self.docker, &deployment)
+            .await?
+            .context("deployment has no root username")?;
+          docker::mongosh_eval(
+            &self.docker,
+            &deployment.container_id,
+            Some(&credentials),
+            &update_password_command(&credentials.username, root_password),
+          )
+          .await
+          .context("set root password")?;
+        }
+
+        let mongodb_port = deployment
+          .port_bindings
+          .as_ref()
+          .and_then(|binding| binding.port);
+        let mut replaced_volume = None;
+        docker::recreate_container(&self.docker, &deployment.container_id, |body| {
+          body.labels.get_or_insert_with(HashMap::new).extend(labels);
+          if let Some(data_volume) = &data_volume {
+            replaced_volume = docker::set_data_volume(body, data_volume);
+          }
+          if root_password.is_some() {
+            let env = body.env.get_or_insert_with(Vec::new);
+            remove_env(env, ROOT_PASSWORD_ENV);
+            set_env(env, ROOT_PASSWORD_FILE_ENV, &root_password_file);
+            add_secrets_tmpfs(body);
+          }
+          if let Some(port) = mongodb_port {
+            pin_port(body, docker::MONGODB_PORT, port);
+          }
+          add_port_bindings(
+            body,
+            docker::MONGODB_PORT,
+            &additional_port_bindings,
+            mongodb_port,
+          );
+          add_port_bindings(body, docker::MONGOT_PORT, &mongot_port_bindings, None);
+          // Kept in the container command so mongod is started with them after every restart
+          if !server_parameters.is_empty() {
+            body
+              .cmd
+              .get_or_insert_with(Vec::new)
+              .extend(set_parameter_args(&server_parameters));
+          }
+          if config_sections.is_some() {
+            body
+              .cmd
+              .get_or_insert_with(Vec::new)
+              .extend(["--config".to_string(), mongod_config_file()]);
+          }
+        })
+        .await?;
+        if let Some(root_password) = &root_password {
+          docker::write_secret(
+            &self.docker,
+            &deployment_name,
+            &root_password_file,
+            root_password,
+          )
+          .await?;
+        }
+        // Best effort, the data of the first start is only left behind as an unused volume
+        if let Some(volume) = replaced_volume {
+          let _ = docker::remove_volume(&self.docker, &volume).await;
+        }
+        if wait_until_healthy {
+          docker::wait_until_healthy(&self.docker, &deployment_name, wait_until_healthy_timeout)
+            .await?;
+        }
+
+        anyhow::Ok(())
+      }
+      .await;
+      if configured.is_err() {
+        let _ = self.delete_deployment(deployment_name.clone()).await;
+      }
+      configured.context("configure deployment")?;
+    }
+
+    if let Some(network) = network {
+      let joined = docker::join_network(&self.docker, &deployment_name, network).await;
+      if joined.is_err() {
+        let _ = self.delete_deployment(deployment_name.clone()).await;
+      }
+      joined.context("create deployment")?;
+    }
+
+    if let Some(oplog_size_mb) = oplog_size_mb {
+      let configured = self
+        .resize_oplog(&deployment_name, oplog_size_mb, wait_until_healthy_timeout)
+        .await;
+      if configured.is_err() {
+        let _ = self.delete_deployment(deployment_name.clone()).await;
+      }
+      configured.context("create deployment")?;
+    }
+
+    if let Some((datasets, create_search_indexes)) = sample_data {
+      let loaded = async {
+        docker::wait_until_healthy(&self.docker, &deployment_name, wait_until_healthy_timeout)
+          .await?;
+        self
+          .load_datasets(
+            &deployment_name,
+            &datasets,
+            create_search_indexes,
+            on_sample_data_progress.as_ref(),
+          )
+          .await
+      }
+      .await;
+      if loaded.is_err() {
+        let _ = self.delete_deployment(deployment_name.clone()).await;
+      }
+      loaded.context("create deployment")?;
+    }
+
+    if !seed.is_empty() {
+      let seeded = async {
+        docker::wait_until_healthy(&self.docker, &deployment_name, wait_until_healthy_timeout)
+          .await?;
+        self.seed(&deployment_name, &seed).await
+      }
+      .await;
+      if seeded.is_err() {
+        let _ = self.delete_deployment(deployment_name.clone()).await;
+      }
+      seeded.context("create deployment")?;
+    }
+
+    let mut init_script_outputs = None;
+    if !init_scripts.is_empty() {
+      let outputs = async {
+        docker::wait_until_healthy(&self.docker, &deployment_name, wait_until_healthy_timeout)
+          .await?;
+        self.run_init_scripts(&deployment_name, &init_scripts).await
+      }
+      .await;
+      if outputs.is_err() {
+        let _ = self.delete_deployment(deployment_name.clone()).await;
+      }
+      init_script_outputs = Some(outputs.context("create deployment")?);
+    }
+
+    let mut deployment = self.get_deployment(deployment_name).await?;
+    deployment.init_script_outputs = init_script_outputs;
+    deployment.generated_credentials = generated_credentials;
+    Ok(deployment)
   }
 
   #[napi]
-  pub async fn list_deployments(&self) -> Result<Vec<Deployment>> {
-    self
+  pub async fn list_deployments(
+    &self,
+    filter: Option<DeploymentFilter>,
+  ) -> Result<Vec<Deployment>> {
+    let deployments = self
       .client
       .list_deployments()
       .await
-      .context("list deployments")
-      .map(|deployments| deployments.into_iter().map(|d| d.into()).collect())
+      .context("list deployments")?;
+
+    let results = join_all(
+      deployments
+        .into_iter()
+        .map(|d| docker::with_container_details(&self.docker, d.into())),
+    )
+    .await;
+    let mut deployments = Vec::new();
+    for result in results {
+      match result {
+        Ok(deployment) => deployments.push(deployment),
+        // Removed while it was being listed
+        Err(error) if docker::is_not_found(&error) => {}
+        Err(error) => return Err(error).context("list deployments"),
+      }
+    }
+
+    Ok(match filter {
+      Some(filter) => deployments
+        .into_iter()
+        .filter(|deployment| matches_filter(deployment, &filter))
+        .collect(),
+      None => deployments,
+    })
+  }
+
+  // Resource limits are changed on the running container, the other settings recreate it with the
+  // same volumes which restarts mongod
+  #[napi]
+  pub fn update_deployment<'env>(
+    &self,
+    env: &'env Env,
+    deployment_name: String,
+    patch: DeploymentPatch,
+  ) -> napi::Result<PromiseRaw<'env, UpdateDeploymentResult>> {
+    let client = self.clone();
+    spawn_with_code(
+      env,
+      async move { client.update(deployment_name, patch).await },
+    )
+  }
+
+  async fn update(
+    &self,
+    deployment_name: String,
+    patch: DeploymentPatch,
+  ) -> Result<UpdateDeploymentResult> {
+    validate_patch(&patch).context("update deployment")?;
+    let deployment = self.get_deployment(deployment_name.clone()).await?;
+
+    if let Some(binding) = &patch.mongodb_port_binding {
+      let current = deployment
+        .port_bindings
+        .iter()
+        .any(|current| current.port.is_some() && current.port == binding.port);
+      if let (Some(port), false) = (binding.port, current) {
+        self.check_port(host_ip(binding)?, port).await?;
+      }
+    }
+
+    let restarted = needs_recreate(&patch);
+    // Resource limits are only changed live when the container isn't recreated anyway
+    if let (Some(resources), false) = (&patch.resources, restarted) {
+      docker::update_resources(&self.docker, &deployment.container_id, resources)
+        .await
+        .context("update deployment")?;
+    }
+
+    if restarted {
+      let current_port = deployment
+        .port_bindings
+        .first()
+        .and_then(|binding| binding.port);
+      docker::recreate_container(&self.docker, &deployment.container_id, |body| {
+        apply_patch(body, &patch, current_port)
+      })
+      .await
+      .context("update deployment")?;
+
+      if deployment.state == State::Running {
+        docker::wait_until_healthy(
+          &self.docker,
+          &deployment_name,
+          Duration::from_secs(DEFAULT_WAIT_UNTIL_HEALTHY_TIMEOUT as u64),
+        )
+        .await
+        .context("update deployment")?;
+      }
+    }
+
+    Ok(UpdateDeploymentResult {
+      restarted,
+      deployment: self.get_deployment(deployment_name).await?,
+    })
+  }
+
+  // The container is renamed in place, it keeps its id, data and configuration so the deployment id
+  // doesn't change
+  // The name is only stored as the container name, no label has to change
+  // The data volume keeps the name it was created with
+  #[napi]
+  pub fn rename_deployment<'env>(
+    &self,
+    env: &'env Env,
+    old_name: String,
+    new_name: String,
+  ) -> napi::Result<PromiseRaw<'env, Deployment>> {
+    let client = self.clone();
+    spawn_with_code(env, async move { client.rename(old_name, new_name).await })
+  }
+
+  async fn rename(&self, old_name: String, new_name: String) -> Result<Deployment> {
+    let deployment = self.get_deployment(old_name.clone()).await?;
+    if old_name == new_name {
+      return Ok(deployment);
+    }
+
+    docker::rename_container(&self.docker, &deployment.container_id, &new_name)
+      .await
+      .context("rename deployment")?;
+    cleanup::rename(&old_name, &new_name);
+
+    self.get_deployment(new_name).await
   }
 
   #[napi]
@@ -54,26 +646,390 @@ impl Client {
       .client
       .delete_deployment(&deployment_name)
       .await
-      .context("delete deployments")
+      .context("delete deployments")?;
+    cleanup::unregister(&deployment_name);
+
+    Ok(())
   }
 
+  // The bulk operations report the result of every matching deployment instead of stopping at the
+  // first failure
   #[napi]
-  pub async fn get_deployment(&self, deployment_name: String) -> Result<Deployment> {
+  pub async fn delete_deployments(
+    &self,
+    filter: DeploymentFilter,
+  ) -> Result<Vec<BulkOperationResult>> {
+    check_bulk_filter(&filter).context("delete deployments")?;
+
+    self
+      .for_each_deployment(filter, |name, _| self.delete_deployment(name))
+      .await
+  }
+
+  #[napi]
+  pub async fn stop_deployments(
+    &self,
+    filter: DeploymentFilter,
+  ) -> Result<Vec<BulkOperationResult>> {
+    check_bulk_filter(&filter).context("stop deployments")?;
+
+    self
+      .for_each_deployment(filter, |_, deployment| async move {
+        if !matches!(
+          deployment.state,
+          State::Running | State::Restarting | State::Paused
+        ) {
+          return Ok(());
+        }
+        docker::stop_container(&self.docker, &deployment.container_id).await
+      })
+      .await
+  }
+
+  // Started deployments are waited for until they are healthy
+  #[napi]
+  pub async fn start_deployments(
+    &self,
+    filter: DeploymentFilter,
+  ) -> Result<Vec<BulkOperationResult>> {
     self
+      .for_each_deployment(filter, |_, deployment| async move {
+        match start_action(&deployment.state) {
+          Some(StartAction::Start) => {
+            docker::start_container(&self.docker, &deployment.container_id).await?
+          }
+          Some(StartAction::Unpause) => {
+            docker::unpause_container(&self.docker, &deployment.container_id).await?
+          }
+          None => return Ok(()),
+        }
+        docker::wait_until_healthy(
+          &self.docker,
+          &deployment.container_id,
+          Duration::from_secs(DEFAULT_WAIT_UNTIL_HEALTHY_TIMEOUT as u64),
+        )
+        .await
+      })
+      .await
+  }
+
+  // Removes the deployments that expired or whose owner process exited
+  // With `dryRun` nothing is removed, the deployments that would be removed are returned
+  #[napi]
+  pub async fn reap_expired_deployments(
+    &self,
+    options: Option<ReapOptions>,
+  ) -> Result<Vec<ReapedDeployment>> {
+    let options = options.unwrap_or(ReapOptions {
+      dry_run: None,
+      creation_source: None,
+    });
+    let deployments = self.list_deployments(None).await?;
+    let hostname = Owner::current().hostname;
+    let now = unix_now()?;
+
+    let mut reaped = Vec::new();
+    for deployment in deployments {
+      let Some(reason) = reap_reason(&deployment, &options, now, |owner| {
+        owner.is_running(&hostname)
+      }) else {
+        continue;
+      };
+      let Some(name) = deployment.name else {
+        continue;
+      };
+
+      if !options.dry_run.unwrap_or_default() {
+        self
+          .client
+          .delete_deployment(&name)
+          .await
+          .with_context(|| format!("reap deployment {name}"))?;
+      }
+      reaped.push(ReapedDeployment { name, reason });
+    }
+
+    Ok(reaped)
+  }
+
+  #[napi]
+  pub async fn get_deployment(&self, deployment_name: String) -> Result<Deployment> {
+    let deployment = self
       .client
       .get_deployment(&deployment_name)
       .await
-      .context("get deployment")
-      .map(|d| d.into())
+      .context("get deployment")?;
+
+    let deployment = docker::with_container_details(&self.docker, deployment.into())
+      .await
+      .context("get deployment")?;
+
+    Ok(docker::with_runtime_details(&self.docker, deployment).await)
   }
 
+  // From the host, the host is picked from the port bindings of the deployment, IPv4 is preferred
+  // unless a family is given
+  // From a network, the alias of the deployment (or its name or IP address) on that network is used
   #[napi]
-  pub async fn get_connection_string(&self, deployment_name: String) -> Result<String> {
-    self
+  pub async fn get_connection_string(
+    &self,
+    deployment_name: String,
+    options: Option<ConnectionStringOptions>,
+  ) -> Result<String> {
+    let connection_string = self
+      .client
+      .get_connection_string(deployment_name.clone())
+      .await
+      .context("get connection string")?;
+    let deployment = self.get_deployment(deployment_name).await?;
+    let options = options.unwrap_or(ConnectionStringOptions {
+      family: None,
+      from: None,
+      network: None,
+      replica_set: None,
+    });
+
+    let connection_string = if options.from == Some(ConnectFrom::Network) {
+      let host = network_host(
+        deployment.name.as_deref(),
+        &deployment.networks,
+        options.network.as_deref(),
+      )
+      .context("get connection string: deployment is not connected to the network")?;
+      with_host(&connection_string, &host)
+    } else {
+      let family = options.family;
+      match (connection_host(&deployment.port_bindings, family), family) {
+        (Some(host), _) => with_host(&connection_string, &host),
+        (None, None) => connection_string,
+        (None, Some(family)) => {
+          anyhow::bail!("get connection string: deployment has no {family:?} port binding")
+        }
+      }
+    };
+
+    if !options.replica_set.unwrap_or_default() {
+      return Ok(connection_string);
+    }
+    let replica_set = deployment
+      .replica_set
+      .context("get connection string: replica set name is only known while running")?;
+    Ok(with_query_option(
+      &connection_string,
+      "replicaSet",
+      &replica_set,
+    ))
+  }
+
+  // Changes the root password in mongod and recreates the container with it, so the connection
+  // string and the credentials used by the client stay valid
+  // Resolves with the new password, when it can't be stored the error carries it instead
+  #[napi]
+  pub fn rotate_root_password<'env>(
+    &self,
+    env: &'env Env,
+    deployment_name: String,
+  ) -> napi::Result<PromiseRaw<'env, RootPasswordRotation>> {
+    let client = self.clone();
+    spawn_with_code(
+      env,
+      async move { client.rotate_password(deployment_name).await },
+    )
+  }
+
+  async fn rotate_password(&self, deployment_name: String) -> Result<RootPasswordRotation> {
+    let deployment = self
       .client
-      .get_connection_string(deployment_name)
+      .get_deployment(&deployment_name)
+      .await
+      .context("get deployment")?;
+    // A password read from the host is written to the tmpfs from its source again after a
+    // restart, a rotated one would be lost
+    if deployment.mongodb_initdb_root_password.is_none() {
+      anyhow::bail!(
+        "rotate root password: deployment has no root password, or reads it from a file"
+      );
+    }
+    let credentials = docker::Credentials::for_deployment(&self.docker, &deployment)
+      .await?
+      .context("rotate root password: deployment has no root username")?;
+    let password = generate_password()?;
+
+    docker::mongosh_eval(
+      &self.docker,
+      &deployment.container_id,
+      Some(&credentials),
+      &update_password_command(&credentials.username, &password),
+    )
+    .await
+    .context("rotate root password")?;
+
+    // Past this point mongod has the new password, failing to store it rejects with it
+    let stored = async {
+      docker::recreate_container(&self.docker, &deployment.container_id, |body| {
+        set_env(
+          body.env.get_or_insert_with(Vec::new),
+          ROOT_PASSWORD_ENV,
+          &password,
+        );
+      })
+      .await?;
+      docker::wait_until_healthy(
+        &self.docker,
+        &deployment_name,
+        Duration::from_secs(DEFAULT_WAIT_UNTIL_HEALTHY_TIMEOUT as u64),
+      )
+      .await
+    }
+    .await;
+
+    if let Err(cause) = stored {
+      return Err(
+        anyhow::Error::new(RootPasswordNotStoredError { password, cause })
+          .context("rotate root password"),
+      );
+    }
+
+    Ok(RootPasswordRotation { password })
+  }
+
+  // Secrets named with `rootPasswordSecret` are resolved by calling the provider with the name of
+  // the secret
+  #[napi]
+  pub fn set_secret_provider(
+    &self,
+    // Weak so that a provider doesn't keep the process alive
+    provider: Option<ThreadsafeFunction<String, Promise<String>, String, Status, false, true>>,
+  ) {
+    if let Ok(mut secret_provider) = self.secret_provider.lock() {
+      *secret_provider = provider.map(Arc::new);
+    }
+  }
+
+  // Sets a server parameter on the running deployment, it is lost when mongod restarts
+  #[napi]
+  pub async fn set_server_parameter(
+    &self,
+    deployment_name: String,
+    key: String,
+    value: Value,
+  ) -> Result<()> {
+    let parameters = Map::from_iter([(key, value)]);
+    validate_runtime_parameters(&parameters).context("set server parameter")?;
+
+    self
+      .set_server_parameters(&deployment_name, &parameters)
+      .await
+      .context("set server parameter")
+  }
+
+  // Loads sample datasets into a running deployment, all of them when none are given
+  #[napi]
+  pub async fn load_sample_data(
+    &self,
+    deployment_name: String,
+    datasets: Option<Vec<String>>,
+    on_progress: Option<
+      ThreadsafeFunction<SampleDataProgress, (), SampleDataProgress, Status, false>,
+    >,
+  ) -> Result<()> {
+    let datasets = resolve_datasets(datasets.as_deref())?;
+
+    self
+      .load_datasets(&deployment_name, &datasets, false, on_progress.as_ref())
+      .await
+      .context("load sample data")
+  }
+
+  // Imports the files in order with mongorestore or mongoimport inside the deployment
+  #[napi]
+  pub async fn seed_deployment(
+    &self,
+    deployment_name: String,
+    seed: Vec<SeedSource>,
+  ) -> Result<()> {
+    seed.iter().try_for_each(validate_seed_source)?;
+
+    self
+      .seed(&deployment_name, &seed)
+      .await
+      .context("seed deployment")
+  }
+
+  #[napi]
+  pub async fn create_user(
+    &self,
+    deployment_name: String,
+    options: CreateUserOptions,
+  ) -> Result<()> {
+    self
+      .run_admin_command(&deployment_name, &create_user_command(&options))
+      .await
+      .with_context(|| format!("create user {}", options.username))?;
+
+    Ok(())
+  }
+
+  #[napi]
+  pub async fn list_users(
+    &self,
+    deployment_name: String,
+    options: Option<ListUsersOptions>,
+  ) -> Result<Vec<DatabaseUser>> {
+    let database = options.and_then(|options| options.database);
+    let response = self
+      .run_admin_command(&deployment_name, &users_info_command(database.as_deref()))
+      .await
+      .context("list users")?;
+
+    parse_users_info(&response).context("list users")
+  }
+
+  #[napi]
+  pub async fn update_user(
+    &self,
+    deployment_name: String,
+    username: String,
+    options: UpdateUserOptions,
+  ) -> Result<()> {
+    self
+      .run_admin_command(&deployment_name, &update_user_command(&username, &options)?)
+      .await
+      .with_context(|| format!("update user {username}"))?;
+
+    Ok(())
+  }
+
+  #[napi]
+  pub async fn drop_user(
+    &self,
+    deployment_name: String,
+    username: String,
+    database: Option<String>,
+  ) -> Result<()> {
+    self
+      .run_admin_command(
+        &deployment_name,
+        &drop_user_command(&username, database.as_deref()),
+      )
+      .await
+      .with_context(|| format!("drop user {username}"))?;
+
+    Ok(())
+  }
+
+  #[napi]
+  pub async fn create_role(
+    &self,
+    deployment_name: String,
+    options: CreateRoleOptions,
+  ) -> Result<()> {
+    self
+      .run_admin_command(&deployment_name, &create_role_command(&options))
       .await
-      .context("get connection string")
+      .with_context(|| format!("create role {}", options.role))?;
+
+    Ok(())
   }
 
   #[napi]
@@ -84,4 +1040,767 @@ impl Client {
       .await
       .context("get deployment id")
   }
+
+  #[napi]
+  pub async fn get_deployment_stats(&self, deployment_name: String) -> Result<DeploymentStats> {
+    docker::deployment_stats(&self.docker, &deployment_name, true)
+      .await
+      .context("get deployment stats")
+  }
+
+  #[napi]
+  pub fn watch_deployment_stats(
+    &self,
+    deployment_name: String,
+    interval_ms: u32,
+  ) -> DeploymentStatsWatcher {
+    DeploymentStatsWatcher {
+      docker: self.docker.clone(),
+      deployment_name,
+      interval: Duration::from_millis(interval_ms as u64),
+      started: AtomicBool::new(false),
+      stopped: AtomicBool::new(false),
+      stop_notify: Notify::new(),
+      disk_usage: Mutex::new(None),
+    }
+  }
+
+  #[napi]
+  pub async fn get_server_status(&self, deployment_name: String) -> Result<ServerStatus> {
+    let deployment = self
+      .client
+      .get_deployment(&deployment_name)
+      .await
+      .context("get deployment")?;
+    let credentials = self.credentials(&deployment).await?;
+
+    docker::mongosh_eval(
+      &self.docker,
+      &deployment.container_id,
+      credentials.as_ref(),
+      "db.adminCommand({ serverStatus: 1 })",
+    )
+    .await
+    .and_then(ServerStatus::try_from)
+    .context("get server status")
+  }
+
+  #[napi]
+  pub async fn get_build_info(&self, deployment_name: String) -> Result<BuildInfo> {
+    let deployment = self
+      .client
+      .get_deployment(&deployment_name)
+      .await
+      .context("get deployment")?;
+    let credentials = self.credentials(&deployment).await?;
+
+    let build_info = docker::mongosh_eval(
+      &self.docker,
+      &deployment.container_id,
+      credentials.as_ref(),
+      "db.adminCommand({ buildInfo: 1 })",
+    )
+    .await
+    .context("get build info")?;
+    let mongot_version = docker::mongot_version(&self.docker, &deployment.container_id).await;
+
+    BuildInfo::try_from_build_info(
+      build_info,
+      deployment.mongodb_version.to_string(),
+      mongot_version,
+    )
+    .context("get build info")
+  }
+
+  #[napi]
+  pub async fn list_images(&self) -> Result<Vec<Image>> {
+    docker::list_images(&self.docker)
+      .await
+      .context("list images")
+  }
+
+  #[napi]
+  pub async fn remove_image(
+    &self,
+    tag_or_digest: String,
+    options: Option<RemoveImageOptions>,
+  ) -> Result<()> {
+    let force = options
+      .and_then(|options| options.force)
+      .unwrap_or_default();
+
+    docker::remove_image(
+      &self.docker,
+      &image_reference(docker::ATLAS_LOCAL_IMAGE, &tag_or_digest),
+      force,
+    )
+    .await
+    .context("remove image")
+  }
+
+  #[napi]
+  pub async fn prune_images(
+    &self,
+    options: Option<PruneImagesOptions>,
+  ) -> Result<PruneImagesResult> {
+    let options = options.unwrap_or(PruneImagesOptions {
+      keep_latest: None,
+      older_than: None,
+    });
+    let images = docker::list_images(&self.docker)
+      .await
+      .context("list images")?;
+    let now = unix_now()?;
+
+    let mut removed: Vec<Image> = Vec::new();
+    for image in prunable_images(&images, &options, now) {
+      docker::remove_image(&self.docker, &image.reference(), false)
+        .await
+        .context("prune images")?;
+      removed.push(image);
+    }
+
+    // Images with multiple tags are listed once per tag but only take up space once
+    let mut reclaimed_ids: Vec<&str> = removed.iter().map(|image| image.id.as_str()).collect();
+    reclaimed_ids.sort();
+    reclaimed_ids.dedup();
+    let reclaimed_bytes = reclaimed_ids
+      .iter()
+      .filter_map(|id| removed.iter().find(|image| image.id == *id))
+      .map(|image| image.size)
+      .sum();
+
+    Ok(PruneImagesResult {
+      removed,
+      reclaimed_bytes,
+    })
+  }
+
+  #[napi]
+  pub async fn resolve_image_tag(&self, tag: String) -> Result<ResolvedImageTag> {
+    ImageTag::try_from(tag.as_str()).map_err(anyhow::Error::msg)?;
+
+    docker::resolve_image_tag(&self.docker, docker::ATLAS_LOCAL_IMAGE, &tag)
+      .await
+      .context("resolve image tag")
+  }
+
+  #[napi]
+  pub async fn plan(&self, spec: DeploymentSpec) -> Result<DeploymentPlan> {
+    let deployments = self.spec_deployments(&spec).await?;
+
+    plan_deployments(&spec, &deployments).context("plan deployments")
+  }
+
+  #[napi]
+  pub fn apply<'env>(
+    &self,
+    env: &'env Env,
+    spec: DeploymentSpec,
+  ) -> napi::Result<PromiseRaw<'env, DeploymentPlan>> {
+    let client = self.clone();
+    spawn_with_code(env, async move { client.apply_spec(spec).await })
+  }
+
+  async fn apply_spec(&self, spec: DeploymentSpec) -> Result<DeploymentPlan> {
+    let deployments = self.spec_deployments(&spec).await?;
+    let plan = plan_deployments(&spec, &deployments).context("plan deployments")?;
+
+    let mut desired: HashMap<String, _> = spec
+      .deployments
+      .into_iter()
+      .filter_map(|options| options.name.clone().map(|name| (name, options)))
+      .collect();
+    for action in &plan.actions {
+      if matches!(
+        action.action,
+        PlannedActionType::Recreate | PlannedActionType::Remove
+      ) {
+        self.delete_deployment(action.name.clone()).await?;
+      }
+      if matches!(
+        action.action,
+        PlannedActionType::Create | PlannedActionType::Recreate
+      ) {
+        if let Some(options) = desired.remove(&action.name) {
+          let labels = HashMap::from([(SPEC_LABEL.to_string(), SPEC_LABEL_VALUE.to_string())]);
+          self.create(options, None, labels).await?;
+        }
+      }
+    }
+
+    Ok(plan)
+  }
+
+  #[napi]
+  pub async fn export_compose(&self, names: Either<String, Vec<String>>) -> Result<String> {
+    let names = match names {
+      Either::A(name) => vec![name],
+      Either::B(names) => names,
+    };
+
+    docker::export_compose(&self.docker, &names)
+      .await
+      .context("export compose")
+  }
+
+  #[napi]
+  pub async fn adopt_container(&self, container_id: String) -> Result<Deployment> {
+    let deployment_name = docker::adopt_container(&self.docker, &container_id)
+      .await
+      .context("adopt container")?;
+
+    self.get_deployment(deployment_name).await
+  }
+
+  #[napi(ts_return_type = "Promise<void>")]
+  #[allow(clippy::type_complexity)]
+  pub fn with_ephemeral_deployment<'env>(
+    &self,
+    env: &'env Env,
+    options: Option<CreateDeploymentOptions>,
+    callback: ThreadsafeFunction<
+      FnArgs<(Deployment, String)>,
+      Promise<()>,
+      FnArgs<(Deployment, String)>,
+      Status,
+      false,
+    >,
+  ) -> napi::Result<PromiseRaw<'env, ()>> {
+    let client = self.clone();
+    spawn_with_code(env, async move {
+      client.with_ephemeral(options, callback).await
+    })
+  }
+
+  async fn with_ephemeral(
+    &self,
+    options: Option<CreateDeploymentOptions>,
+    callback: EphemeralCallback,
+  ) -> Result<()> {
+    let (deployment, connection_string) = self.create_ephemeral(options).await?;
+    let deployment_name = deployment.name.clone().unwrap_or_default();
+
+    let result = async {
+      callback
+        .call_async(FnArgs::from((deployment, connection_string)))
+        .await?
+        .await
+    }
+    .await;
+
+    // The deployment is removed whether the callback succeeded or not
+    let deleted = self.delete_deployment(deployment_name).await;
+
+    result
+      .map_err(|error| anyhow::Error::msg(error.to_string()))
+      .context("run ephemeral deployment callback")?;
+    deleted
+  }
+
+  #[napi]
+  pub fn create_ephemeral_deployment<'env>(
+    &self,
+    env: &'env Env,
+    options: Option<CreateDeploymentOptions>,
+  ) -> napi::Result<PromiseRaw<'env, EphemeralDeployment>> {
+    let client = self.clone();
+    spawn_with_code(env, async move {
+      let (deployment, connection_string) = client.create_ephemeral(options).await?;
+
+      Ok(EphemeralDeployment {
+        name: deployment.name.unwrap_or_default(),
+        connection_string,
+        client: client.client.clone(),
+        disposed: tokio::sync::Mutex::new(false),
+      })
+    })
+  }
+}
+
+impl Client {
+  // Deployments named in the spec are compared with their runtime details
+  async fn spec_deployments(&self, spec: &DeploymentSpec) -> Result<Vec<Deployment>> {
+    let deployments = self.list_deployments(None).await?;
+
+    Ok(
+      join_all(deployments.into_iter().map(|deployment| async {
+        let in_spec = spec
+          .deployments
+          .iter()
+          .any(|options| options.name.is_some() && options.name == deployment.name);
+        if in_spec {
+          docker::with_runtime_details(&self.docker, deployment).await
+        } else {
+          deployment
+        }
+      }))
+      .await,
+    )
+  }
+
+  // Fails with a `PortInUseError` when a requested port is taken
+  // With a port range the first free port of the range is used
+  async fn select_port(&self, options: &mut CreateDeploymentOptions) -> Result<()> {
+    let requested = options
+      .additional_port_bindings
+      .iter()
+      .flatten()
+      .chain(&options.mongot_port_binding);
+    for binding in requested {
+      if let Some(port) = binding.port {
+        self.check_port(host_ip(binding)?, port).await?;
+      }
+    }
+
+    let port_range = options.port_range.take();
+    if port_range.is_none()
+      && options
+        .mongodb_port_binding
+        .as_ref()
+        .is_none_or(|binding| binding.port.is_none())
+    {
+      return Ok(());
+    }
+
+    let binding = options
+      .mongodb_port_binding
+      .get_or_insert_with(|| MongoDBPortBinding {
+        binding_type: BindingType::Loopback,
+        ip: "127.0.0.1".to_string(),
+        port: None,
+      });
+    let ip = host_ip(binding)?;
+
+    match (port_range, binding.port) {
+      (Some(_), Some(_)) => anyhow::bail!("set either a port or a port range, not both"),
+      (Some(port_range), None) => {
+        binding.port = Some(first_free_port(port_range, |port| is_port_free(ip, port))?);
+      }
+      (None, Some(port)) => self.check_port(ip, port).await?,
+      (None, None) => {}
+    }
+
+    Ok(())
+  }
+
+  // The atlas-local image picks the name of the replica set, a different name is rejected
+  async fn resize_oplog(
+    &self,
+    deployment_name: &str,
+    oplog_size_mb: u32,
+    timeout: Duration,
+  ) -> Result<()> {
+    // mongod has to be up to be configured
+    docker::wait_until_healthy(&self.docker, deployment_name, timeout).await?;
+    let deployment = self
+      .client
+      .get_deployment(deployment_name)
+      .await
+      .context("get deployment")?;
+
+    let credentials = self.credentials(&deployment).await?;
+    docker::mongosh_eval(
+      &self.docker,
+      &deployment.container_id,
+      credentials.as_ref(),
+      &resize_oplog_command(oplog_size_mb),
+    )
+    .await
+    .context("resize oplog")?;
+
+    Ok(())
+  }
+
+  // The tmpfs the root password is kept in is emptied when the container stops, when the password
+  // can't be read it is written again from where it was read on the host
+  async fn credentials(
+    &self,
+    deployment: &atlas_local::models::Deployment,
+  ) -> Result<Option<docker::Credentials>> {
+    let credentials = docker::Credentials::for_deployment(&self.docker, deployment).await;
+    if credentials.is_ok() {
+      return credentials;
+    }
+    let labels = docker::container_labels(&self.docker, &deployment.container_id).await?;
+    let Some(source) = secret_source_from_labels(&labels) else {
+      return credentials;
+    };
+
+    let password = self.read_secret(&source).await?;
+    docker::write_secret(
+      &self.docker,
+      &deployment.container_id,
+      &secret_file(ROOT_PASSWORD_SECRET),
+      &password,
+    )
+    .await
+    .context("restore root password")?;
+    docker::Credentials::for_deployment(&self.docker, deployment).await
+  }
+
+  async fn read_secret(&self, source: &SecretSource) -> Result<String> {
+    match source {
+      SecretSource::File(path) => read_secret_file(path),
+      SecretSource::Env(name) => read_secret_env(name),
+      SecretSource::Provider(name) => {
+        let provider = self
+          .secret_provider
+          .lock()
+          .ok()
+          .and_then(|provider| provider.clone())
+          .with_context(|| format!("get secret {name}: no secret provider is set"))?;
+
+        async { provider.call_async(name.clone()).await?.await }
+          .await
+          .map_err(|error| anyhow::Error::msg(error.to_string()))
+          .with_context(|| format!("get secret {name}"))
+      }
+    }
+  }
+
+  // Runs a command as the root user of the deployment
+  async fn run_admin_command(&self, deployment_name: &str, expression: &str) -> Result<Value> {
+    let deployment = self
+      .client
+      .get_deployment(deployment_name)
+      .await
+      .context("get deployment")?;
+    let credentials = self.credentials(&deployment).await?;
+
+    docker::mongosh_eval(
+      &self.docker,
+      &deployment.container_id,
+      credentials.as_ref(),
+      expression,
+    )
+    .await
+    .and_then(check_command_response)
+  }
+
+  // Runs setParameter on the running mongod
+  async fn set_server_parameters(
+    &self,
+    deployment_name: &str,
+    parameters: &Map<String, Value>,
+  ) -> Result<()> {
+    let deployment = self
+      .client
+      .get_deployment(deployment_name)
+      .await
+      .context("get deployment")?;
+    let credentials = self.credentials(&deployment).await?;
+
+    docker::mongosh_eval(
+      &self.docker,
+      &deployment.container_id,
+      credentials.as_ref(),
+      &set_parameter_command(parameters),
+    )
+    .await
+    .context("set server parameters")?;
+
+    Ok(())
+  }
+
+  async fn load_datasets(
+    &self,
+    deployment_name: &str,
+    datasets: &[String],
+    create_search_indexes: bool,
+    on_progress: Option<
+      &ThreadsafeFunction<SampleDataProgress, (), SampleDataProgress, Status, false>,
+    >,
+  ) -> Result<()> {
+    let deployment = self
+      .client
+      .get_deployment(deployment_name)
+      .await
+      .context("get deployment")?;
+    let credentials = self.credentials(&deployment).await?;
+
+    docker::load_sample_data(
+      &self.docker,
+      &deployment.container_id,
+      credentials.as_ref(),
+      datasets,
+      create_search_indexes,
+      |progress| {
+        if let Some(on_progress) = on_progress {
+          on_progress.call(progress, ThreadsafeFunctionCallMode::NonBlocking);
+        }
+      },
+    )
+    .await
+  }
+
+  async fn seed(&self, deployment_name: &str, seed: &[SeedSource]) -> Result<()> {
+    let deployment = self
+      .client
+      .get_deployment(deployment_name)
+      .await
+      .context("get deployment")?;
+    let credentials = self.credentials(&deployment).await?;
+
+    for source in seed {
+      docker::seed(
+        &self.docker,
+        &deployment.container_id,
+        credentials.as_ref(),
+        source,
+      )
+      .await?;
+    }
+
+    Ok(())
+  }
+
+  // Stops at the first failing script
+  async fn run_init_scripts(
+    &self,
+    deployment_name: &str,
+    init_scripts: &[ResolvedInitScript],
+  ) -> Result<Vec<InitScriptOutput>> {
+    let deployment = self
+      .client
+      .get_deployment(deployment_name)
+      .await
+      .context("get deployment")?;
+    let credentials = self.credentials(&deployment).await?;
+
+    let mut outputs = Vec::new();
+    for init_script in init_scripts {
+      outputs.push(
+        docker::run_init_script(
+          &self.docker,
+          &deployment.container_id,
+          credentials.as_ref(),
+          deployment.mongodb_initdb_database.as_deref(),
+          init_script,
+        )
+        .await?,
+      );
+    }
+
+    Ok(outputs)
+  }
+
+  // Runs `operation` on the deployments matching `filter`, a few at a time
+  async fn for_each_deployment<F, Fut>(
+    &self,
+    filter: DeploymentFilter,
+    operation: F,
+  ) -> Result<Vec<BulkOperationResult>>
+  where
+    F: Fn(String, Deployment) -> Fut,
+    Fut: Future<Output = Result<()>>,
+  {
+    let deployments = self.list_deployments(Some(filter)).await?;
+
+    Ok(
+      stream::iter(deployments)
+        .map(|deployment| {
+          let name = deployment
+            .name
+            .clone()
+            .unwrap_or_else(|| deployment.container_id.clone());
+          let result = operation(name.clone(), deployment);
+          async move { BulkOperationResult::new(name, result.await) }
+        })
+        .buffered(BULK_CONCURRENCY)
+        .collect()
+        .await,
+    )
+  }
+
+  async fn check_port(&self, ip: IpAddr, port: u16) -> Result<()> {
+    if is_port_free(ip, port) {
+      return Ok(());
+    }
+
+    let container = docker::container_using_port(&self.docker, port)
+      .await
+      .unwrap_or_default();
+    // Scanning the processes reads a lot of files
+    let process = tokio::task::spawn_blocking(move || process_using_port(port))
+      .await
+      .unwrap_or_default();
+    Err(
+      PortInUseError {
+        ip: ip.to_string(),
+        port,
+        container,
+        process,
+      }
+      .into(),
+    )
+  }
+
+  // Creates a uniquely named deployment, `options.name` is used as the prefix of the name
+  // The deployment is removed when the process exits
+  async fn create_ephemeral(
+    &self,
+    options: Option<CreateDeploymentOptions>,
+  ) -> Result<(Deployment, String)> {
+    let mut options = options.unwrap_or_default();
+    let deployment_name = ephemeral::unique_name(options.name.as_deref().unwrap_or("ephemeral"));
+    options.name = Some(deployment_name.clone());
+    options.wait_until_healthy = Some(true);
+    options.auto_remove_on_exit = Some(true);
+
+    let created = async {
+      let deployment = self.create(options, None, HashMap::new()).await?;
+      let connection_string = self
+        .get_connection_string(deployment_name.clone(), None)
+        .await?;
+
+      Ok::<_, anyhow::Error>((deployment, connection_string))
+    }
+    .await;
+
+    if created.is_err() {
+      // Creation can fail after the container has been created, e.g. when it never gets healthy
+      // When removing fails it is left to the exit hook
+      let _ = self.delete_deployment(deployment_name.clone()).await;
+    }
+
+    created.context("create ephemeral deployment")
+  }
+}
+
+fn unix_now() -> Result<i64> {
+  Ok(
+    SystemTime::now()
+      .duration_since(UNIX_EPOCH)
+      .context("get current time")?
+      .as_secs() as i64,
+  )
+}
+
+#[napi]
+pub struct EphemeralDeployment {
+  pub name: String,
+  pub connection_string: String,
+  client: Arc<AtlasLocalClient>,
+  // Held while deleting so concurrent calls delete only once
+  disposed: tokio::sync::Mutex<bool>,
+}
+
+#[napi]
+impl EphemeralDeployment {
+  #[napi]
+  pub async fn dispose(&self) -> Result<()> {
+    let mut disposed = self.disposed.lock().await;
+    if *disposed {
+      return Ok(());
+    }
+
+    // Only marked as disposed once deleted so a failed delete can be retried
+    self
+      .client
+      .delete_deployment(&self.name)
+      .await
+      .context("dispose ephemeral deployment")?;
+    cleanup::unregister(&self.name);
+    *disposed = true;
+
+    Ok(())
+  }
+}
+
+#[napi]
+pub struct DeploymentStatsWatcher {
+  docker: Docker,
+  deployment_name: String,
+  interval: Duration,
+  started: AtomicBool,
+  stopped: AtomicBool,
+  // Wakes up a `next` waiting for the interval when the watcher is stopped
+  stop_notify: Notify,
+  // Last disk usage sample and when it was taken
+  disk_usage: Mutex<Option<(Instant, Option<i64>)>>,
+}
+
+#[napi]
+impl DeploymentStatsWatcher {
+  // Resolves with the next sample, waiting for the interval between samples
+  // Resolves with null once the watcher has been stopped
+  #[napi]
+  pub async fn next(&self) -> Result<Option<DeploymentStats>> {
+    if self.started.swap(true, Ordering::SeqCst) && !self.stopped.load(Ordering::SeqCst) {
+      tokio::select! {
+        _ = tokio::time::sleep(self.interval) => {}
+        _ = self.stop_notify.notified() => {}
+      }
+    }
+
+    if self.stopped.load(Ordering::SeqCst) {
+      return Ok(None);
+    }
+
+    let last_disk_usage = self.disk_usage.lock().ok().and_then(|last| *last);
+    let measure_disk_usage =
+      last_disk_usage.is_none_or(|(measured_at, _)| measured_at.elapsed() >= DISK_USAGE_INTERVAL);
+
+    let mut stats =
+      docker::deployment_stats(&self.docker, &self.deployment_name, measure_disk_usage)
+        .await
+        .context("watch deployment stats")?;
+    match last_disk_usage {
+      Some((_, disk_usage)) if !measure_disk_usage => stats.data_disk_usage = disk_usage,
+      _ => {
+        if let Ok(mut last) = self.disk_usage.lock() {
+          *last = Some((Instant::now(), stats.data_disk_usage));
+        }
+      }
+    }
+
+    Ok(Some(stats))
+  }
+
+  #[napi]
+  pub fn stop(&self) {
+    self.stopped.store(true, Ordering::SeqCst);
+    // Stores a permit when `next` isn't waiting yet, so it doesn't start waiting afterwards
+    self.stop_notify.notify_one();
+  }
+}
+
+#[napi]
+pub fn parse_image_tag(tag: String) -> Result<ParsedImageTag> {
+  models::image_tag::parse_image_tag(&tag).context("parse image tag")
+}
+
+#[napi]
+pub fn parse_compose_service(
+  yaml: String,
+  service_name: Option<String>,
+) -> Result<ImportedDeployment> {
+  models::import_deployment::parse_compose_service(&yaml, service_name.as_deref())
+    .context("parse compose service")
+}
+
+#[napi]
+pub fn parse_docker_run(command: String) -> Result<ImportedDeployment> {
+  models::import_deployment::parse_docker_run(&command).context("parse docker run")
+}
+
+// Reads a JSON or YAML spec file to pass to `plan` or `apply`
+#[napi(ts_return_type = "DeploymentSpec")]
+pub fn load_deployment_spec(path: String) -> Result<Value> {
+  let content =
+    std::fs::read_to_string(&path).with_context(|| format!("read deployment spec {path}"))?;
+
+  models::deployment_spec::parse_deployment_spec(&content)
+    .with_context(|| format!("load deployment spec {path}"))
+}
+
+#[napi(js_name = "compareMongoDBVersions")]
+pub fn compare_mongodb_versions(a: String, b: String) -> Result<i32> {
+  models::image_tag::compare_versions(&a, &b).context("compare MongoDB versions")
+}
+
+#[napi]
+pub fn satisfies_version(version: String, range: String) -> Result<bool> {
+  models::image_tag::satisfies_version(&version, &range).context("check version range")
 }
@@ -0,0 +1,139 @@
+use anyhow::{Context, Result};
+use napi_derive::napi;
+use serde_json::Value;
+
+// Characters of generated credentials, they need no escaping in connection strings or shells
+const CREDENTIAL_ALPHABET: &[u8] =
+  b"ABCDEFGHIJKLMNOPQRSTUVWXYZabcdefghijklmnopqrstuvwxyz0123456789";
+
+const GENERATED_USERNAME_LENGTH: usize = 12;
+const GENERATED_PASSWORD_LENGTH: usize = 32;
+
+pub(crate) const ROOT_PASSWORD_ENV: &str = "MONGODB_INITDB_ROOT_PASSWORD";
+
+#[napi(object)]
+#[derive(Debug)]
+pub struct AuthOptions {
+  // Generates a random root username and password
+  pub generate: Option<bool>,
+}
+
+#[napi(object)]
+#[derive(Clone, PartialEq, Debug)]
+pub struct GeneratedCredentials {
+  pub username: String,
+  pub password: String,
+}
+
+#[napi(object)]
+pub struct RootPasswordRotation {
+  pub password: String,
+}
+
+fn fill_random(bytes: &mut [u8]) -> Result<()> {
+  getrandom::fill(bytes)
+    .map_err(|error| anyhow::Error::msg(error.to_string()))
+    .context("generate random bytes")
+}
+
+pub(crate) fn random_string(length: usize) -> Result<String> {
+  let mut bytes = vec![0u8; length];
+  fill_random(&mut bytes)?;
+
+  // 256 is not a multiple of the alphabet size, bytes above the last full multiple are redrawn so
+  // every character is equally likely
+  let limit = 256 - 256 % CREDENTIAL_ALPHABET.len();
+  let mut characters = String::with_capacity(length);
+  for mut byte in bytes {
+    while byte as usize >= limit {
+      let mut redraw = [0u8; 1];
+      fill_random(&mut redraw)?;
+      byte = redraw[0];
+    }
+    characters.push(CREDENTIAL_ALPHABET[byte as usize % CREDENTIAL_ALPHABET.len()] as char);
+  }
+
+  Ok(characters)
+}
+
+pub(crate) fn generate_password() -> Result<String> {
+  random_string(GENERATED_PASSWORD_LENGTH)
+}
+
+pub(crate) fn generate_credentials() -> Result<GeneratedCredentials> {
+  Ok(GeneratedCredentials {
+    username: format!("admin_{}", random_string(GENERATED_USERNAME_LENGTH)?),
+    password: generate_password()?,
+  })
+}
+
+pub(crate) fn update_password_command(username: &str, password: &str) -> String {
+  format!(
+    "db.getSiblingDB(\"admin\").runCommand({{ updateUser: {}, pwd: {} }})",
+    Value::String(username.to_string()),
+    Value::String(password.to_string())
+  )
+}
+
+// Sets `key` in a Docker environment list, replacing an existing value
+pub(crate) fn set_env(env: &mut Vec<String>, key: &str, value: &str) {
+  remove_env(env, key);
+  env.push(format!("{key}={value}"));
+}
+
+pub(crate) fn remove_env(env: &mut Vec<String>, key: &str) {
+  let prefix = format!("{key}=");
+  env.retain(|variable| !variable.starts_with(&prefix));
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  #[test]
+  fn test_random_string() {
+    let first = random_string(32).unwrap();
+    let second = random_string(32).unwrap();
+
+    assert_eq!(first.len(), 32);
+    assert!(first
+      .bytes()
+      .all(|byte| CREDENTIAL_ALPHABET.contains(&byte)));
+    assert_ne!(first, second);
+  }
+
+  #[test]
+  fn test_generate_credentials() {
+    let credentials = generate_credentials().unwrap();
+
+    assert!(credentials.username.starts_with("admin_"));
+    assert_eq!(credentials.username.len(), 18);
+    assert_eq!(credentials.password.len(), 32);
+  }
+
+  #[test]
+  fn test_update_password_command() {
+    assert_eq!(
+      update_password_command("admin", "s3cr\"t"),
+      "db.getSiblingDB(\"admin\").runCommand({ updateUser: \"admin\", pwd: \"s3cr\\\"t\" })"
+    );
+  }
+
+  #[test]
+  fn test_set_env() {
+    let mut env = vec![
+      "MONGODB_INITDB_ROOT_USERNAME=admin".to_string(),
+      "MONGODB_INITDB_ROOT_PASSWORD=old".to_string(),
+    ];
+
+    set_env(&mut env, ROOT_PASSWORD_ENV, "new");
+
+    assert_eq!(
+      env,
+      vec![
+        "MONGODB_INITDB_ROOT_USERNAME=admin",
+        "MONGODB_INITDB_ROOT_PASSWORD=new"
+      ]
+    );
+  }
+}
@@ -0,0 +1,359 @@
+use std::collections::BTreeMap;
+
+use anyhow::{Context, Result};
+use bollard::models::{ContainerInspectResponse, ImageInspect};
+use serde::Serialize;
+
+use crate::models::images::{digest_from_repo_digest, tag_from_reference, unpinned_repository};
+
+#[derive(Serialize, Default, PartialEq, Debug)]
+pub(crate) struct ComposeFile {
+  pub services: BTreeMap<String, ComposeService>,
+  #[serde(skip_serializing_if = "BTreeMap::is_empty")]
+  pub volumes: BTreeMap<String, ComposeVolume>,
+}
+
+#[derive(Serialize, Default, PartialEq, Debug)]
+pub(crate) struct ComposeService {
+  pub image: String,
+  pub container_name: String,
+  #[serde(skip_serializing_if = "Vec::is_empty")]
+  pub command: Vec<String>,
+  #[serde(skip_serializing_if = "BTreeMap::is_empty")]
+  pub environment: BTreeMap<String, String>,
+  #[serde(skip_serializing_if = "Vec::is_empty")]
+  pub ports: Vec<String>,
+  #[serde(skip_serializing_if = "Vec::is_empty")]
+  pub volumes: Vec<String>,
+  #[serde(skip_serializing_if = "BTreeMap::is_empty")]
+  pub labels: BTreeMap<String, String>,
+}
+
+#[derive(Serialize, Default, PartialEq, Debug)]
+pub(crate) struct ComposeVolume {}
+
+impl ComposeFile {
+  // Adds the service of a deployment container, `image` is the image the container was created from
+  // The command, environment variables and labels inherited from the image are left out
+  pub(crate) fn add_service(&mut self, container: ContainerInspectResponse, image: ImageInspect) {
+    let name = container
+      .name
+      .as_deref()
+      .unwrap_or_default()
+      .trim_start_matches('/')
+      .to_string();
+    let config = container.config.unwrap_or_default();
+    let image_config = image.config.unwrap_or_default();
+
+    // Server parameters are passed to mongod as arguments of the container command
+    let command = config
+      .cmd
+      .filter(|cmd| image_config.cmd.as_ref() != Some(cmd))
+      .unwrap_or_default();
+
+    let image_env = image_config.env.unwrap_or_default();
+    let environment = config
+      .env
+      .unwrap_or_default()
+      .into_iter()
+      .filter(|variable| !image_env.contains(variable))
+      .filter_map(|variable| {
+        variable
+          .split_once('=')
+          .map(|(key, value)| (key.to_string(), value.to_string()))
+      })
+      .collect();
+
+    let image_labels = image_config.labels.unwrap_or_default();
+    let labels = config
+      .labels
+      .unwrap_or_default()
+      .into_iter()
+      .filter(|(key, value)| image_labels.get(key) != Some(value))
+      .collect();
+
+    let mut ports: Vec<String> = container
+      .host_config
+      .and_then(|host_config| host_config.port_bindings)
+      .unwrap_or_default()
+      .into_iter()
+      .flat_map(|(container_port, bindings)| {
+        let container_port = container_port
+          .split('/')
+          .next()
+          .unwrap_or_default()
+          .to_string();
+        bindings
+          .unwrap_or_default()
+          .into_iter()
+          .map(move |binding| port_mapping(binding.host_ip, binding.host_port, &container_port))
+      })
+      .collect();
+    ports.sort();
+
+    let mut volumes = Vec::new();
+    for mount in container.mounts.unwrap_or_default() {
+      let Some(destination) = mount.destination else {
+        continue;
+      };
+      match (mount.typ.as_deref(), mount.name, mount.source) {
+        (Some("volume"), Some(volume), _) if !is_anonymous_volume(&volume) => {
+          volumes.push(format!("{volume}:{destination}"));
+          self.volumes.insert(volume, ComposeVolume {});
+        }
+        (Some("bind"), _, Some(source)) => {
+          volumes.push(format!("{source}:{destination}"));
+        }
+        _ => volumes.push(destination),
+      }
+    }
+    volumes.sort();
+
+    self.services.insert(
+      name.clone(),
+      ComposeService {
+        image: portable_image(
+          config.image.unwrap_or_default(),
+          &image.repo_digests.unwrap_or_default(),
+        ),
+        container_name: name,
+        command,
+        environment,
+        ports,
+        volumes,
+        labels,
+      },
+    );
+  }
+
+  pub(crate) fn to_yaml(&self) -> Result<String> {
+    serde_yaml::to_string(self).context("serialize docker compose file")
+  }
+}
+
+// Pinned images are tagged in a repository that only exists on this machine, they are exported as
+// the digest of the repository they were pinned from
+fn portable_image(reference: String, repo_digests: &[String]) -> String {
+  let repository = match tag_from_reference(&reference) {
+    Some(tag) => reference
+      .strip_suffix(&format!(":{tag}"))
+      .unwrap_or(&reference),
+    None => reference.split('@').next().unwrap_or(&reference),
+  };
+  let unpinned = unpinned_repository(repository);
+  if unpinned == repository {
+    return reference;
+  }
+
+  repo_digests
+    .iter()
+    .find_map(|repo_digest| digest_from_repo_digest(repo_digest, unpinned))
+    .map(|digest| format!("{unpinned}@{digest}"))
+    .unwrap_or(reference)
+}
+
+fn port_mapping(
+  host_ip: Option<String>,
+  host_port: Option<String>,
+  container_port: &str,
+) -> String {
+  let host_port = host_port.filter(|port| !port.is_empty());
+  match (host_ip.as_deref().filter(|ip| !ip.is_empty()), host_port) {
+    (Some(ip), Some(port)) if ip.contains(':') => format!("[{ip}]:{port}:{container_port}"),
+    (Some(ip), Some(port)) => format!("{ip}:{port}:{container_port}"),
+    (Some(ip), None) if ip.contains(':') => format!("[{ip}]::{container_port}"),
+    (Some(ip), None) => format!("{ip}::{container_port}"),
+    (None, Some(port)) => format!("{port}:{container_port}"),
+    (None, None) => container_port.to_string(),
+  }
+}
+
+// Docker names anonymous volumes with 64 hexadecimal characters
+pub(crate) fn is_anonymous_volume(name: &str) -> bool {
+  name.len() == 64 && name.chars().all(|c| c.is_ascii_hexdigit())
+}
+
+#[cfg(test)]
+mod tests {
+  use std::collections::HashMap;
+
+  use bollard::models::{ContainerConfig, HostConfig, ImageConfig, MountPoint, PortBinding};
+
+  use super::*;
+
+  fn container() -> ContainerInspectResponse {
+    ContainerInspectResponse {
+      name: Some("/test_deployment".to_string()),
+      config: Some(ContainerConfig {
+        image: Some("mongodb/mongodb-atlas-local:8.0.4".to_string()),
+        cmd: Some(vec![
+          "--setParameter".to_string(),
+          "notablescan=true".to_string(),
+        ]),
+        env: Some(vec![
+          "PATH=/usr/local/bin".to_string(),
+          "MONGODB_INITDB_ROOT_USERNAME=admin".to_string(),
+          "DO_NOT_TRACK=true".to_string(),
+        ]),
+        labels: Some(HashMap::from([
+          ("version".to_string(), "8.0.4".to_string()),
+          ("mongodb-atlas-local".to_string(), "container".to_string()),
+          ("creationSource".to_string(), "MCPSERVER".to_string()),
+        ])),
+        ..Default::default()
+      }),
+      host_config: Some(HostConfig {
+        port_bindings: Some(HashMap::from([(
+          "27017/tcp".to_string(),
+          Some(vec![PortBinding {
+            host_ip: Some("127.0.0.1".to_string()),
+            host_port: Some("27018".to_string()),
+          }]),
+        )])),
+        ..Default::default()
+      }),
+      mounts: Some(vec![
+        MountPoint {
+          typ: Some("volume".to_string()),
+          name: Some("test_deployment_data".to_string()),
+          destination: Some("/data/db".to_string()),
+          ..Default::default()
+        },
+        MountPoint {
+          typ: Some("volume".to_string()),
+          name: Some("a".repeat(64)),
+          destination: Some("/data/configdb".to_string()),
+          ..Default::default()
+        },
+        MountPoint {
+          typ: Some("bind".to_string()),
+          source: Some("/host/seed-data".to_string()),
+          destination: Some("/docker-entrypoint-initdb.d".to_string()),
+          ..Default::default()
+        },
+      ]),
+      ..Default::default()
+    }
+  }
+
+  fn image() -> ImageInspect {
+    ImageInspect {
+      repo_digests: Some(vec![
+        "mongodb/mongodb-atlas-local@sha256:0123456789abcdef0123456789abcdef".to_string(),
+      ]),
+      config: Some(ImageConfig {
+        cmd: Some(vec!["mongod".to_string()]),
+        env: Some(vec!["PATH=/usr/local/bin".to_string()]),
+        labels: Some(HashMap::from([(
+          "version".to_string(),
+          "8.0.4".to_string(),
+        )])),
+        ..Default::default()
+      }),
+      ..Default::default()
+    }
+  }
+
+  #[test]
+  fn test_compose_service_from_container() {
+    let mut compose = ComposeFile::default();
+    compose.add_service(container(), image());
+
+    let service = compose.services.get("test_deployment").unwrap();
+    assert_eq!(service.image, "mongodb/mongodb-atlas-local:8.0.4");
+    assert_eq!(service.container_name, "test_deployment");
+    assert_eq!(
+      service.command,
+      vec!["--setParameter".to_string(), "notablescan=true".to_string()]
+    );
+    assert_eq!(
+      service.environment,
+      BTreeMap::from([
+        ("DO_NOT_TRACK".to_string(), "true".to_string()),
+        (
+          "MONGODB_INITDB_ROOT_USERNAME".to_string(),
+          "admin".to_string()
+        ),
+      ])
+    );
+    assert_eq!(service.ports, vec!["127.0.0.1:27018:27017".to_string()]);
+    assert_eq!(
+      service.volumes,
+      vec![
+        "/data/configdb".to_string(),
+        "/host/seed-data:/docker-entrypoint-initdb.d".to_string(),
+        "test_deployment_data:/data/db".to_string(),
+      ]
+    );
+    assert_eq!(
+      service.labels,
+      BTreeMap::from([
+        ("creationSource".to_string(), "MCPSERVER".to_string()),
+        ("mongodb-atlas-local".to_string(), "container".to_string()),
+      ])
+    );
+    assert_eq!(
+      compose.volumes,
+      BTreeMap::from([("test_deployment_data".to_string(), ComposeVolume {})])
+    );
+  }
+
+  #[test]
+  fn test_compose_service_from_pinned_container() {
+    let mut container = container();
+    if let Some(config) = container.config.as_mut() {
+      config.image = Some("mongodb/mongodb-atlas-local-pinned-0123456789abcdef:8.0.4".to_string());
+      config.cmd = Some(vec!["mongod".to_string()]);
+    }
+
+    let mut compose = ComposeFile::default();
+    compose.add_service(container, image());
+
+    let service = compose.services.get("test_deployment").unwrap();
+    assert_eq!(
+      service.image,
+      "mongodb/mongodb-atlas-local@sha256:0123456789abcdef0123456789abcdef"
+    );
+    assert!(service.command.is_empty());
+  }
+
+  #[test]
+  fn test_compose_file_to_yaml() {
+    let mut compose = ComposeFile::default();
+    compose.add_service(container(), image());
+
+    let yaml = compose.to_yaml().unwrap();
+
+    assert!(yaml.starts_with("services:\n  test_deployment:\n"));
+    assert!(yaml.contains("    image: mongodb/mongodb-atlas-local:8.0.4\n"));
+    assert!(yaml.contains("    command:\n    - --setParameter\n    - notablescan=true\n"));
+    assert!(yaml.contains("127.0.0.1:27018:27017"));
+    assert!(yaml.contains("    - /data/configdb\n"));
+    assert!(yaml.contains("volumes:\n  test_deployment_data: {}\n"));
+  }
+
+  #[test]
+  fn test_port_mapping() {
+    assert_eq!(
+      port_mapping(
+        Some("127.0.0.1".to_string()),
+        Some("27017".to_string()),
+        "27017"
+      ),
+      "127.0.0.1:27017:27017"
+    );
+    assert_eq!(
+      port_mapping(Some("::1".to_string()), Some("27017".to_string()), "27017"),
+      "[::1]:27017:27017"
+    );
+    assert_eq!(
+      port_mapping(Some("0.0.0.0".to_string()), None, "27017"),
+      "0.0.0.0::27017"
+    );
+    assert_eq!(
+      port_mapping(None, Some("27017".to_string()), "27017"),
+      "27017:27017"
+    );
+    assert_eq!(port_mapping(None, None, "27017"), "27017");
+  }
+}
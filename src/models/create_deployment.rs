@@ -1,9 +1,18 @@
+use crate::models::auth::AuthOptions;
+use crate::models::init_scripts::InitScript;
 use crate::models::list_deployments::{CreationSource, MongoDBPortBinding};
+use crate::models::networks::NetworkOptions;
+use crate::models::ports::PortRange;
+use crate::models::sample_data::SampleDataOptions;
+use crate::models::seed::SeedSource;
 use atlas_local::models::ImageTag;
 use napi_derive::napi;
+use serde_json::Value;
+use std::collections::HashMap;
 use std::time::Duration;
 
 #[napi(object)]
+#[derive(Default, Debug)]
 pub struct CreateDeploymentOptions {
   // Identifiers
   pub name: Option<String>,
@@ -12,20 +21,47 @@ pub struct CreateDeploymentOptions {
   pub image: Option<String>,
   pub skip_pull_image: Option<bool>,
   pub image_tag: Option<String>,
+  pub image_digest: Option<String>,
 
   // Creation Options
   pub wait_until_healthy: Option<bool>,
   pub wait_until_healthy_timeout: Option<u32>,
   pub creation_source: Option<CreationSource>,
 
+  // Cleanup
+  pub ttl: Option<u32>,
+  pub expires_at: Option<i64>,
+  pub remove_when_owner_exits: Option<bool>,
+  // Removed while the process exits, which delays the exit by up to 10 seconds, a deployment
+  // that isn't removed by then is left behind
+  pub auto_remove_on_exit: Option<bool>,
+
   // Initial database configuration
   pub local_seed_location: Option<String>,
+  // Named volume the data directory is kept in instead of an anonymous one, it is left in place
+  // when the deployment is deleted and a deployment created with it again starts from its data
+  pub data_volume: Option<String>,
+  /// Run in order with `docker exec` once the deployment is healthy, after `seed`
+  /// Unlike scripts in the init directory of the image, they run after the deployment reports
+  /// healthy and on every `createDeployment`, also when `dataVolume` already holds data
+  pub init_scripts: Option<Vec<InitScript>>,
+  // Imported once the deployment is healthy, before the init scripts run
+  pub seed: Option<Vec<SeedSource>>,
   pub load_sample_data: Option<bool>,
+  // Loads only the given datasets, unlike `loadSampleData` which loads all of them
+  pub sample_data: Option<SampleDataOptions>,
   pub mongodb_initdb_database: Option<String>,
   pub mongodb_initdb_root_password_file: Option<String>,
   pub mongodb_initdb_root_password: Option<String>,
+  // Read on the host and kept in a tmpfs of the container instead of its environment
+  pub root_password_from_file: Option<String>,
+  pub root_password_from_env: Option<String>,
+  // Name of the secret to ask the secret provider of the client for
+  pub root_password_secret: Option<String>,
   pub mongodb_initdb_root_username_file: Option<String>,
   pub mongodb_initdb_root_username: Option<String>,
+  // Replaces setting the root username and password
+  pub auth: Option<AuthOptions>,
   pub voyage_api_key: Option<String>,
 
   // Logging
@@ -38,6 +74,21 @@ pub struct CreateDeploymentOptions {
 
   // Port configuration
   pub mongodb_port_binding: Option<MongoDBPortBinding>,
+  pub additional_port_bindings: Option<Vec<MongoDBPortBinding>>,
+  pub mongot_port_binding: Option<MongoDBPortBinding>,
+  pub port_range: Option<PortRange>,
+
+  // Network configuration
+  pub network: Option<NetworkOptions>,
+
+  // Replica set configuration
+  #[napi(js_name = "oplogSizeMB")]
+  pub oplog_size_mb: Option<u32>,
+
+  // Server configuration
+  // An object or mongod.conf YAML, merged into the options the image starts mongod with
+  pub mongod_config: Option<Value>,
+  pub set_parameters: Option<HashMap<String, Value>>,
 }
 
 impl TryFrom<CreateDeploymentOptions> for atlas_local::models::CreateDeploymentOptions {
@@ -95,19 +146,32 @@ mod tests {
       image: Some("mongodb/mongodb-atlas-local".to_string()),
       skip_pull_image: Some(false),
       image_tag: Some("8.0.0".to_string()),
+      image_digest: None,
       wait_until_healthy: Some(true),
       wait_until_healthy_timeout: Some(30),
       creation_source: Some(CreationSource {
         source_type: CreationSourceType::MCPServer,
         source: "MCPSERVER".to_string(),
       }),
+      ttl: None,
+      expires_at: None,
+      remove_when_owner_exits: None,
+      auto_remove_on_exit: None,
       local_seed_location: Some("/host/seed-data".to_string()),
+      data_volume: None,
+      init_scripts: None,
+      seed: None,
       load_sample_data: Some(true),
+      sample_data: None,
       mongodb_initdb_database: Some("testdb".to_string()),
       mongodb_initdb_root_password_file: Some("/run/secrets/password".to_string()),
       mongodb_initdb_root_password: Some("password123".to_string()),
+      root_password_from_file: None,
+      root_password_from_env: None,
+      root_password_secret: None,
       mongodb_initdb_root_username_file: Some("/run/secrets/username".to_string()),
       mongodb_initdb_root_username: Some("admin".to_string()),
+      auth: None,
       voyage_api_key: Some("voyage_api_key".to_string()),
       mongot_log_file: Some("/tmp/mongot.log".to_string()),
       runner_log_file: Some("/tmp/runner.log".to_string()),
@@ -118,6 +182,13 @@ mod tests {
         ip: "127.0.0.1".to_string(),
         port: Some(27017),
       }),
+      additional_port_bindings: None,
+      mongot_port_binding: None,
+      port_range: None,
+      network: None,
+      oplog_size_mb: None,
+      mongod_config: None,
+      set_parameters: None,
     };
     let lib_create_deployment_options: atlas_local::models::CreateDeploymentOptions =
       create_deployment_options.try_into().unwrap();
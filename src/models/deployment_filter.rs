@@ -0,0 +1,247 @@
+use std::collections::HashMap;
+
+use anyhow::Result;
+use napi_derive::napi;
+
+use crate::models::list_deployments::{CreationSource, Deployment, State};
+
+// Deployments changed at the same time by the bulk operations
+pub(crate) const BULK_CONCURRENCY: usize = 8;
+
+// Every given criterion has to match
+#[napi(object)]
+pub struct DeploymentFilter {
+  pub state: Option<State>,
+  pub creation_source: Option<CreationSource>,
+  // Every label has to be set to the given value
+  pub labels: Option<HashMap<String, String>>,
+  // `*` matches any characters and `?` a single character
+  pub name_pattern: Option<String>,
+  // Without any criterion a filter matches every deployment on the host, including the ones of
+  // other tools, deleting or stopping all of them has to be asked for with `all`
+  pub all: Option<bool>,
+}
+
+impl DeploymentFilter {
+  fn has_criteria(&self) -> bool {
+    self.state.is_some()
+      || self.creation_source.is_some()
+      || self.labels.is_some()
+      || self.name_pattern.is_some()
+  }
+}
+
+pub(crate) fn check_bulk_filter(filter: &DeploymentFilter) -> Result<()> {
+  if !filter.has_criteria() && filter.all != Some(true) {
+    anyhow::bail!("the filter has no criteria, set all to match every deployment");
+  }
+
+  Ok(())
+}
+
+#[napi(object)]
+#[derive(PartialEq, Debug)]
+pub struct BulkOperationResult {
+  pub name: String,
+  pub success: bool,
+  pub error: Option<String>,
+}
+
+impl BulkOperationResult {
+  pub(crate) fn new(name: String, result: anyhow::Result<()>) -> Self {
+    match result {
+      Ok(()) => BulkOperationResult {
+        name,
+        success: true,
+        error: None,
+      },
+      Err(error) => BulkOperationResult {
+        name,
+        success: false,
+        error: Some(format!("{error:#}")),
+      },
+    }
+  }
+}
+
+pub(crate) fn matches_filter(deployment: &Deployment, filter: &DeploymentFilter) -> bool {
+  if filter
+    .state
+    .as_ref()
+    .is_some_and(|state| *state != deployment.state)
+  {
+    return false;
+  }
+  if filter.creation_source.is_some() && deployment.creation_source != filter.creation_source {
+    return false;
+  }
+  if let Some(labels) = &filter.labels {
+    if !labels
+      .iter()
+      .all(|(key, value)| deployment.labels.get(key) == Some(value))
+    {
+      return false;
+    }
+  }
+  if let Some(pattern) = &filter.name_pattern {
+    let name = deployment.name.as_deref().unwrap_or_default();
+    if !matches_pattern(pattern, name) {
+      return false;
+    }
+  }
+
+  true
+}
+
+fn matches_pattern(pattern: &str, name: &str) -> bool {
+  let pattern: Vec<char> = pattern.chars().collect();
+  let name: Vec<char> = name.chars().collect();
+
+  // Position after the last `*` and the name position it was matched up to, to backtrack to
+  let mut star: Option<(usize, usize)> = None;
+  let (mut p, mut n) = (0, 0);
+  while n < name.len() {
+    if p < pattern.len() && (pattern[p] == '?' || pattern[p] == name[n]) {
+      p += 1;
+      n += 1;
+    } else if p < pattern.len() && pattern[p] == '*' {
+      star = Some((p + 1, n));
+      p += 1;
+    } else if let Some((star_p, star_n)) = star {
+      p = star_p;
+      n = star_n + 1;
+      star = Some((star_p, n));
+    } else {
+      return false;
+    }
+  }
+
+  pattern[p..].iter().all(|c| *c == '*')
+}
+
+// How `startDeployments` brings a deployment up, a paused container can't be started
+#[derive(PartialEq, Debug)]
+pub(crate) enum StartAction {
+  Start,
+  Unpause,
+}
+
+pub(crate) fn start_action(state: &State) -> Option<StartAction> {
+  match state {
+    State::Running => None,
+    State::Paused => Some(StartAction::Unpause),
+    _ => Some(StartAction::Start),
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+  use crate::models::list_deployments::{test_deployment, CreationSourceType};
+
+  fn deployment(name: &str) -> Deployment {
+    Deployment {
+      labels: HashMap::from([("ci".to_string(), "true".to_string())]),
+      creation_source: Some(CreationSource {
+        source_type: CreationSourceType::AtlasLocal,
+        source: "ATLASLOCAL".to_string(),
+      }),
+      ..test_deployment(name)
+    }
+  }
+
+  fn filter() -> DeploymentFilter {
+    DeploymentFilter {
+      state: None,
+      creation_source: None,
+      labels: None,
+      name_pattern: None,
+      all: None,
+    }
+  }
+
+  #[test]
+  fn test_matches_pattern() {
+    assert!(matches_pattern("ci-*", "ci-1234"));
+    assert!(matches_pattern("ci-????", "ci-1234"));
+    assert!(matches_pattern("*-test-*", "ci-test-1"));
+    assert!(matches_pattern("*", ""));
+    assert!(!matches_pattern("ci-*", "local-ci-1"));
+    assert!(!matches_pattern("ci-???", "ci-1234"));
+  }
+
+  #[test]
+  fn test_matches_filter() {
+    let deployment = deployment("ci-1234");
+
+    assert!(matches_filter(&deployment, &filter()));
+    assert!(matches_filter(
+      &deployment,
+      &DeploymentFilter {
+        state: Some(State::Running),
+        labels: Some(HashMap::from([("ci".to_string(), "true".to_string())])),
+        name_pattern: Some("ci-*".to_string()),
+        ..filter()
+      }
+    ));
+    assert!(!matches_filter(
+      &deployment,
+      &DeploymentFilter {
+        state: Some(State::Exited),
+        ..filter()
+      }
+    ));
+    assert!(!matches_filter(
+      &deployment,
+      &DeploymentFilter {
+        labels: Some(HashMap::from([("ci".to_string(), "false".to_string())])),
+        ..filter()
+      }
+    ));
+    assert!(!matches_filter(
+      &deployment,
+      &DeploymentFilter {
+        creation_source: Some(CreationSource {
+          source_type: CreationSourceType::MCPServer,
+          source: "MCPSERVER".to_string(),
+        }),
+        ..filter()
+      }
+    ));
+  }
+
+  #[test]
+  fn test_check_bulk_filter() {
+    assert!(check_bulk_filter(&filter()).is_err());
+    assert!(check_bulk_filter(&DeploymentFilter {
+      all: Some(true),
+      ..filter()
+    })
+    .is_ok());
+    assert!(check_bulk_filter(&DeploymentFilter {
+      name_pattern: Some("ci-*".to_string()),
+      ..filter()
+    })
+    .is_ok());
+  }
+
+  #[test]
+  fn test_bulk_operation_result() {
+    assert_eq!(
+      BulkOperationResult::new("ci-1".to_string(), Err(anyhow::anyhow!("not found"))),
+      BulkOperationResult {
+        name: "ci-1".to_string(),
+        success: false,
+        error: Some("not found".to_string()),
+      }
+    );
+  }
+
+  #[test]
+  fn test_start_action() {
+    assert_eq!(start_action(&State::Running), None);
+    assert_eq!(start_action(&State::Paused), Some(StartAction::Unpause));
+    assert_eq!(start_action(&State::Exited), Some(StartAction::Start));
+    assert_eq!(start_action(&State::Created), Some(StartAction::Start));
+  }
+}
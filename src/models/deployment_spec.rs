@@ -0,0 +1,616 @@
+use anyhow::{Context, Result};
+use napi_derive::napi;
+use serde_json::Value;
+
+use crate::models::create_deployment::CreateDeploymentOptions;
+use crate::models::list_deployments::{BindingType, Deployment, MongoDBPortBinding};
+use crate::models::sample_data::resolve_datasets;
+use crate::models::server_parameters::parse_mongod_config;
+
+// Set on the deployments created by `apply`, `removeExtra` only removes deployments carrying it
+pub(crate) const SPEC_LABEL: &str = "mongodb-atlas-local.spec";
+pub(crate) const SPEC_LABEL_VALUE: &str = "apply";
+
+const DATA_LOSS: &str = "the deployment is deleted, its data is lost";
+
+#[napi(object)]
+pub struct DeploymentSpec {
+  pub deployments: Vec<CreateDeploymentOptions>,
+  pub remove_extra: Option<bool>,
+}
+
+#[napi(object)]
+#[derive(PartialEq, Debug)]
+pub struct DeploymentPlan {
+  pub actions: Vec<PlannedAction>,
+}
+
+#[napi(object)]
+#[derive(PartialEq, Debug)]
+pub struct PlannedAction {
+  pub name: String,
+  pub action: PlannedActionType,
+  pub reasons: Vec<String>,
+}
+
+#[napi(string_enum)]
+#[derive(PartialEq, Debug)]
+pub enum PlannedActionType {
+  Create,
+  Recreate,
+  Remove,
+  Unchanged,
+}
+
+// Specs are checked into repositories as JSON or YAML, YAML being a superset of JSON both are
+// parsed the same way
+// The options are only checked against `DeploymentSpec` once the spec is passed to `plan` or `apply`
+pub(crate) fn parse_deployment_spec(content: &str) -> Result<Value> {
+  let spec: Value = serde_yaml::from_str(content).context("parse deployment spec")?;
+  if !spec.get("deployments").is_some_and(Value::is_array) {
+    anyhow::bail!("a deployment spec needs a deployments list");
+  }
+
+  Ok(spec)
+}
+
+// Diffs the desired deployments against the existing ones
+// Deployments are matched by name, only the options set in the spec are compared, `deployments`
+// need their runtime details for `sampleData` to be compared
+pub(crate) fn plan_deployments(
+  spec: &DeploymentSpec,
+  deployments: &[Deployment],
+) -> Result<DeploymentPlan> {
+  let mut actions = Vec::new();
+
+  for desired in &spec.deployments {
+    let Some(name) = &desired.name else {
+      anyhow::bail!("every deployment in the spec needs a name");
+    };
+    if actions
+      .iter()
+      .any(|action: &PlannedAction| &action.name == name)
+    {
+      anyhow::bail!("deployment {name} is specified more than once");
+    }
+    check_comparable(desired).with_context(|| format!("deployment {name}"))?;
+
+    let action = match deployments
+      .iter()
+      .find(|deployment| deployment.name.as_ref() == Some(name))
+    {
+      None => PlannedAction {
+        name: name.clone(),
+        action: PlannedActionType::Create,
+        reasons: vec!["deployment does not exist".to_string()],
+      },
+      Some(deployment) => {
+        let mut reasons =
+          drift(desired, deployment).with_context(|| format!("deployment {name}"))?;
+        if reasons.is_empty() {
+          PlannedAction {
+            name: name.clone(),
+            action: PlannedActionType::Unchanged,
+            reasons,
+          }
+        } else {
+          reasons.push(DATA_LOSS.to_string());
+          PlannedAction {
+            name: name.clone(),
+            action: PlannedActionType::Recreate,
+            reasons,
+          }
+        }
+      }
+    };
+    actions.push(action);
+  }
+
+  if spec.remove_extra.unwrap_or_default() {
+    // Deployments created some other way, by the Atlas CLI or by hand, are left alone
+    for name in deployments
+      .iter()
+      .filter(|deployment| {
+        deployment.labels.get(SPEC_LABEL).map(String::as_str) == Some(SPEC_LABEL_VALUE)
+      })
+      .filter_map(|deployment| deployment.name.as_ref())
+    {
+      if !actions.iter().any(|action| &action.name == name) {
+        actions.push(PlannedAction {
+          name: name.clone(),
+          action: PlannedActionType::Remove,
+          reasons: vec![
+            "deployment is not in the spec".to_string(),
+            DATA_LOSS.to_string(),
+          ],
+        });
+      }
+    }
+  }
+
+  Ok(DeploymentPlan { actions })
+}
+
+// Options that only take effect while a deployment is created and can't be read back from it,
+// a spec with them would be reported unchanged whatever they are set to
+fn check_comparable(desired: &CreateDeploymentOptions) -> Result<()> {
+  let options = [
+    ("ttl", desired.ttl.is_some()),
+    ("initScripts", desired.init_scripts.is_some()),
+    ("seed", desired.seed.is_some()),
+    ("auth", desired.auth.is_some()),
+    (
+      "rootPasswordFromFile",
+      desired.root_password_from_file.is_some(),
+    ),
+    (
+      "rootPasswordFromEnv",
+      desired.root_password_from_env.is_some(),
+    ),
+    ("rootPasswordSecret", desired.root_password_secret.is_some()),
+  ];
+  let unsupported: Vec<&str> = options
+    .iter()
+    .filter(|(_, set)| *set)
+    .map(|(option, _)| *option)
+    .collect();
+  if !unsupported.is_empty() {
+    anyhow::bail!(
+      "{} can't be compared with an existing deployment, it is not supported in specs",
+      unsupported.join(", ")
+    );
+  }
+
+  Ok(())
+}
+
+fn drift(desired: &CreateDeploymentOptions, actual: &Deployment) -> Result<Vec<String>> {
+  let mut reasons = Vec::new();
+  let mut compare = |field: &str, differs: bool| {
+    if differs {
+      reasons.push(format!("{field} differs"));
+    }
+  };
+
+  compare("image", differs(&desired.image, &actual.image));
+  compare("imageTag", differs(&desired.image_tag, &actual.image_tag));
+  compare(
+    "imageDigest",
+    differs(&desired.image_digest, &actual.image_digest),
+  );
+  compare(
+    "oplogSizeMB",
+    differs(&desired.oplog_size_mb, &actual.oplog_size_mb),
+  );
+  compare(
+    "creationSource",
+    differs(&desired.creation_source, &actual.creation_source),
+  );
+  compare(
+    "localSeedLocation",
+    differs(&desired.local_seed_location, &actual.local_seed_location),
+  );
+  compare(
+    "dataVolume",
+    differs(&desired.data_volume, &actual.data_volume),
+  );
+  compare(
+    "loadSampleData",
+    differs(&desired.load_sample_data, &actual.mongodb_load_sample_data),
+  );
+  compare(
+    "mongodbInitdbDatabase",
+    differs(
+      &desired.mongodb_initdb_database,
+      &actual.mongodb_initdb_database,
+    ),
+  );
+  compare(
+    "mongodbInitdbRootPasswordFile",
+    differs(
+      &desired.mongodb_initdb_root_password_file,
+      &actual.mongodb_initdb_root_password_file,
+    ),
+  );
+  compare(
+    "mongodbInitdbRootPassword",
+    differs(
+      &desired.mongodb_initdb_root_password,
+      &actual.mongodb_initdb_root_password,
+    ),
+  );
+  compare(
+    "mongodbInitdbRootUsernameFile",
+    differs(
+      &desired.mongodb_initdb_root_username_file,
+      &actual.mongodb_initdb_root_username_file,
+    ),
+  );
+  compare(
+    "mongodbInitdbRootUsername",
+    differs(
+      &desired.mongodb_initdb_root_username,
+      &actual.mongodb_initdb_root_username,
+    ),
+  );
+  compare(
+    "voyageApiKey",
+    differs(&desired.voyage_api_key, &actual.voyage_api_key),
+  );
+  compare(
+    "mongotLogFile",
+    differs(&desired.mongot_log_file, &actual.mongot_log_file),
+  );
+  compare(
+    "runnerLogFile",
+    differs(&desired.runner_log_file, &actual.runner_log_file),
+  );
+  compare(
+    "doNotTrack",
+    differs(&desired.do_not_track, &Some(actual.do_not_track)),
+  );
+  compare(
+    "telemetryBaseUrl",
+    differs(&desired.telemetry_base_url, &actual.telemetry_base_url),
+  );
+  compare(
+    "mongodbPortBinding",
+    desired
+      .mongodb_port_binding
+      .iter()
+      .any(|binding| !has_port_binding(&actual.port_bindings, binding)),
+  );
+  compare(
+    "additionalPortBindings",
+    desired
+      .additional_port_bindings
+      .iter()
+      .flatten()
+      .any(|binding| !has_port_binding(&actual.port_bindings, binding)),
+  );
+  compare(
+    "mongotPortBinding",
+    desired
+      .mongot_port_binding
+      .iter()
+      .any(|binding| !has_port_binding(&actual.mongot_port_bindings, binding)),
+  );
+  compare(
+    "network",
+    desired.network.as_ref().is_some_and(|network| {
+      !actual.networks.iter().any(|actual| {
+        actual.name == network.name
+          && network
+            .aliases
+            .iter()
+            .flatten()
+            .all(|alias| actual.aliases.contains(alias))
+      })
+    }),
+  );
+  compare(
+    "expiresAt",
+    differs(&desired.expires_at, &actual.expires_at),
+  );
+  let mongod_config = desired
+    .mongod_config
+    .clone()
+    .map(parse_mongod_config)
+    .transpose()?;
+  compare(
+    "mongodConfig",
+    differs(&mongod_config, &actual.mongod_config),
+  );
+  compare(
+    "setParameters",
+    differs(&desired.set_parameters, &actual.set_parameters),
+  );
+  // Whether search indexes were created isn't recorded, only the datasets are compared
+  if let Some(sample_data) = &desired.sample_data {
+    let datasets = resolve_datasets(sample_data.datasets.as_deref())?;
+    compare(
+      "sampleData",
+      datasets
+        .iter()
+        .any(|dataset| !actual.sample_datasets.contains(dataset)),
+    );
+  }
+
+  Ok(reasons)
+}
+
+// Unset options use the defaults of the deployment, they never cause drift
+fn differs<T: PartialEq>(desired: &Option<T>, actual: &Option<T>) -> bool {
+  desired.is_some() && desired != actual
+}
+
+fn has_port_binding(actual: &[MongoDBPortBinding], desired: &MongoDBPortBinding) -> bool {
+  actual.iter().any(|actual| {
+    desired.binding_type == actual.binding_type
+      && (desired.binding_type != BindingType::Specific || desired.ip == actual.ip)
+      && (desired.port.is_none() || desired.port == actual.port)
+  })
+}
+
+#[cfg(test)]
+mod tests {
+  use std::collections::HashMap;
+
+  use serde_json::json;
+
+  use crate::models::list_deployments::test_deployment;
+  use crate::models::networks::NetworkOptions;
+  use crate::models::sample_data::SampleDataOptions;
+
+  use super::*;
+
+  fn options(name: &str) -> CreateDeploymentOptions {
+    CreateDeploymentOptions {
+      name: Some(name.to_string()),
+      ..Default::default()
+    }
+  }
+
+  fn deployment(name: &str) -> Deployment {
+    Deployment {
+      port_bindings: vec![MongoDBPortBinding {
+        binding_type: BindingType::Loopback,
+        ip: "127.0.0.1".to_string(),
+        port: Some(27017),
+      }],
+      image: Some("mongodb/mongodb-atlas-local".to_string()),
+      image_tag: Some("8.0.4".to_string()),
+      image_digest: Some("sha256:digest".to_string()),
+      ..test_deployment(name)
+    }
+  }
+
+  #[test]
+  fn test_plan_deployments_create_missing() {
+    let spec = DeploymentSpec {
+      deployments: vec![options("test_deployment")],
+      remove_extra: None,
+    };
+
+    let plan = plan_deployments(&spec, &[]).unwrap();
+
+    assert_eq!(
+      plan.actions,
+      vec![PlannedAction {
+        name: "test_deployment".to_string(),
+        action: PlannedActionType::Create,
+        reasons: vec!["deployment does not exist".to_string()],
+      }]
+    );
+  }
+
+  #[test]
+  fn test_plan_deployments_unchanged() {
+    let mut desired = options("test_deployment");
+    desired.image = Some("mongodb/mongodb-atlas-local".to_string());
+    desired.image_tag = Some("8.0.4".to_string());
+    desired.image_digest = Some("sha256:digest".to_string());
+    desired.do_not_track = Some(false);
+    desired.mongodb_port_binding = Some(MongoDBPortBinding {
+      binding_type: BindingType::Loopback,
+      ip: "127.0.0.1".to_string(),
+      port: None,
+    });
+    let spec = DeploymentSpec {
+      deployments: vec![desired],
+      remove_extra: None,
+    };
+
+    let plan = plan_deployments(&spec, &[deployment("test_deployment")]).unwrap();
+
+    assert_eq!(plan.actions.len(), 1);
+    assert_eq!(plan.actions[0].action, PlannedActionType::Unchanged);
+    assert!(plan.actions[0].reasons.is_empty());
+  }
+
+  #[test]
+  fn test_plan_deployments_recreate_drifted() {
+    let mut desired = options("test_deployment");
+    desired.image = Some("registry.local/mongodb-atlas-local".to_string());
+    desired.image_tag = Some("8.0.5".to_string());
+    desired.voyage_api_key = Some("voyage_api_key".to_string());
+    desired.mongodb_port_binding = Some(MongoDBPortBinding {
+      binding_type: BindingType::Loopback,
+      ip: "127.0.0.1".to_string(),
+      port: Some(27018),
+    });
+    let spec = DeploymentSpec {
+      deployments: vec![desired],
+      remove_extra: None,
+    };
+
+    let plan = plan_deployments(&spec, &[deployment("test_deployment")]).unwrap();
+
+    assert_eq!(
+      plan.actions,
+      vec![PlannedAction {
+        name: "test_deployment".to_string(),
+        action: PlannedActionType::Recreate,
+        reasons: vec![
+          "image differs".to_string(),
+          "imageTag differs".to_string(),
+          "voyageApiKey differs".to_string(),
+          "mongodbPortBinding differs".to_string(),
+          DATA_LOSS.to_string(),
+        ],
+      }]
+    );
+  }
+
+  #[test]
+  fn test_plan_deployments_remove_extra() {
+    let spec = DeploymentSpec {
+      deployments: vec![options("test_deployment")],
+      remove_extra: Some(true),
+    };
+
+    let mut extra = deployment("extra_deployment");
+    extra
+      .labels
+      .insert(SPEC_LABEL.to_string(), SPEC_LABEL_VALUE.to_string());
+
+    let plan = plan_deployments(
+      &spec,
+      &[
+        deployment("test_deployment"),
+        extra,
+        deployment("atlas_cli_deployment"),
+      ],
+    )
+    .unwrap();
+
+    assert_eq!(plan.actions.len(), 2);
+    assert_eq!(plan.actions[0].action, PlannedActionType::Unchanged);
+    assert_eq!(
+      plan.actions[1],
+      PlannedAction {
+        name: "extra_deployment".to_string(),
+        action: PlannedActionType::Remove,
+        reasons: vec![
+          "deployment is not in the spec".to_string(),
+          DATA_LOSS.to_string(),
+        ],
+      }
+    );
+  }
+
+  #[test]
+  fn test_plan_deployments_compares_later_options() {
+    let mut desired = options("test_deployment");
+    desired.network = Some(NetworkOptions {
+      name: "test_network".to_string(),
+      aliases: None,
+      create: None,
+    });
+    desired.sample_data = Some(SampleDataOptions {
+      datasets: Some(vec!["sample_mflix".to_string()]),
+      create_search_indexes: None,
+    });
+    desired.set_parameters = Some(HashMap::from([(
+      "notablescan".to_string(),
+      Value::Bool(true),
+    )]));
+    desired.expires_at = Some(4600);
+    let spec = DeploymentSpec {
+      deployments: vec![desired],
+      remove_extra: None,
+    };
+
+    let plan = plan_deployments(&spec, &[deployment("test_deployment")]).unwrap();
+
+    assert_eq!(
+      plan.actions[0].reasons,
+      vec![
+        "network differs".to_string(),
+        "expiresAt differs".to_string(),
+        "setParameters differs".to_string(),
+        "sampleData differs".to_string(),
+        DATA_LOSS.to_string(),
+      ]
+    );
+  }
+
+  #[test]
+  fn test_plan_deployments_rejects_options_it_cannot_compare() {
+    let mut desired = options("test_deployment");
+    desired.ttl = Some(60);
+    desired.seed = Some(vec![]);
+    let spec = DeploymentSpec {
+      deployments: vec![desired],
+      remove_extra: None,
+    };
+
+    let error = plan_deployments(&spec, &[]).unwrap_err();
+
+    assert!(format!("{error:#}").contains("ttl, seed can't be compared"));
+  }
+
+  #[test]
+  fn test_plan_deployments_keeps_extra_by_default() {
+    let spec = DeploymentSpec {
+      deployments: vec![],
+      remove_extra: None,
+    };
+
+    let plan = plan_deployments(&spec, &[deployment("extra_deployment")]).unwrap();
+
+    assert!(plan.actions.is_empty());
+  }
+
+  #[test]
+  fn test_plan_deployments_requires_name() {
+    let mut desired = options("test_deployment");
+    desired.name = None;
+    let spec = DeploymentSpec {
+      deployments: vec![desired],
+      remove_extra: None,
+    };
+
+    assert!(plan_deployments(&spec, &[]).is_err());
+  }
+
+  #[test]
+  fn test_plan_deployments_rejects_duplicates() {
+    let spec = DeploymentSpec {
+      deployments: vec![options("test_deployment"), options("test_deployment")],
+      remove_extra: None,
+    };
+
+    assert!(plan_deployments(&spec, &[]).is_err());
+  }
+
+  #[test]
+  fn test_parse_deployment_spec_json() {
+    let spec = parse_deployment_spec(
+      r#"{ "deployments": [{ "name": "test_deployment", "imageTag": "8.0.4" }], "removeExtra": true }"#,
+    )
+    .unwrap();
+
+    assert_eq!(
+      spec,
+      json!({
+        "deployments": [{ "name": "test_deployment", "imageTag": "8.0.4" }],
+        "removeExtra": true,
+      })
+    );
+  }
+
+  #[test]
+  fn test_parse_deployment_spec_yaml() {
+    let spec = parse_deployment_spec(
+      r#"
+deployments:
+  - name: test_deployment
+    imageTag: "8.0.4"
+    mongodbPortBinding:
+      type: Loopback
+      ip: 127.0.0.1
+      port: 27017
+"#,
+    )
+    .unwrap();
+
+    assert_eq!(
+      spec,
+      json!({
+        "deployments": [{
+          "name": "test_deployment",
+          "imageTag": "8.0.4",
+          "mongodbPortBinding": { "type": "Loopback", "ip": "127.0.0.1", "port": 27017 },
+        }],
+      })
+    );
+  }
+
+  #[test]
+  fn test_parse_deployment_spec_requires_deployments() {
+    assert!(parse_deployment_spec("removeExtra: true").is_err());
+    assert!(parse_deployment_spec("deployments: test_deployment").is_err());
+    assert!(parse_deployment_spec("deployments: [").is_err());
+  }
+}
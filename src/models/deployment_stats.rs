@@ -0,0 +1,263 @@
+use std::time::Duration;
+
+use bollard::models::{ContainerMemoryStats, ContainerStatsResponse};
+use napi_derive::napi;
+
+// Measuring the disk usage walks the whole data directory, watchers reuse a sample for this long
+pub(crate) const DISK_USAGE_INTERVAL: Duration = Duration::from_secs(60);
+
+#[napi(object)]
+pub struct DeploymentStats {
+  // CPU
+  pub cpu_percentage: f64,
+
+  // Memory
+  pub memory_usage: i64,
+  pub memory_limit: i64,
+  pub memory_percentage: f64,
+  pub oom_killed: bool,
+
+  // Network
+  pub network_rx_bytes: i64,
+  pub network_tx_bytes: i64,
+
+  // Block IO
+  pub block_read_bytes: i64,
+  pub block_write_bytes: i64,
+
+  // Processes
+  pub pids: u32,
+
+  // Disk usage of the data directory
+  pub data_disk_usage: Option<i64>,
+}
+
+impl From<ContainerStatsResponse> for DeploymentStats {
+  fn from(source: ContainerStatsResponse) -> Self {
+    let memory_usage = source
+      .memory_stats
+      .as_ref()
+      .map(memory_usage_without_cache)
+      .unwrap_or_default();
+    let memory_limit = source
+      .memory_stats
+      .as_ref()
+      .and_then(|memory| memory.limit)
+      .unwrap_or_default();
+
+    let (network_rx_bytes, network_tx_bytes) = source
+      .networks
+      .iter()
+      .flat_map(|networks| networks.values())
+      .fold((0, 0), |(rx, tx), network| {
+        (
+          rx + network.rx_bytes.unwrap_or_default(),
+          tx + network.tx_bytes.unwrap_or_default(),
+        )
+      });
+
+    let (block_read_bytes, block_write_bytes) = source
+      .blkio_stats
+      .iter()
+      .flat_map(|blkio| blkio.io_service_bytes_recursive.iter().flatten())
+      .fold((0, 0), |(read, write), entry| {
+        let value = entry.value.unwrap_or_default();
+        match entry.op.as_deref().map(str::to_lowercase).as_deref() {
+          Some("read") => (read + value, write),
+          Some("write") => (read, write + value),
+          _ => (read, write),
+        }
+      });
+
+    Self {
+      cpu_percentage: cpu_percentage(&source),
+      memory_usage: memory_usage as i64,
+      memory_limit: memory_limit as i64,
+      memory_percentage: percentage(memory_usage as f64, memory_limit as f64),
+      oom_killed: false,
+      network_rx_bytes: network_rx_bytes as i64,
+      network_tx_bytes: network_tx_bytes as i64,
+      block_read_bytes: block_read_bytes as i64,
+      block_write_bytes: block_write_bytes as i64,
+      pids: source
+        .pids_stats
+        .and_then(|pids| pids.current)
+        .unwrap_or_default() as u32,
+      data_disk_usage: None,
+    }
+  }
+}
+
+// Same calculation as `docker stats`, the delta between the current and the previous sample
+// of the container CPU usage relative to the host CPU usage, scaled by the number of CPUs
+fn cpu_percentage(source: &ContainerStatsResponse) -> f64 {
+  let (Some(cpu), Some(precpu)) = (&source.cpu_stats, &source.precpu_stats) else {
+    return 0.0;
+  };
+
+  let total_usage = |stats: &bollard::models::ContainerCpuStats| {
+    stats
+      .cpu_usage
+      .as_ref()
+      .and_then(|usage| usage.total_usage)
+      .unwrap_or_default() as f64
+  };
+  let cpu_delta = total_usage(cpu) - total_usage(precpu);
+  let system_delta = cpu.system_cpu_usage.unwrap_or_default() as f64
+    - precpu.system_cpu_usage.unwrap_or_default() as f64;
+
+  let online_cpus = cpu.online_cpus.map(|cpus| cpus as f64).unwrap_or_else(|| {
+    cpu
+      .cpu_usage
+      .as_ref()
+      .and_then(|usage| usage.percpu_usage.as_ref())
+      .map(|percpu| percpu.len() as f64)
+      .unwrap_or(1.0)
+  });
+
+  if cpu_delta <= 0.0 || system_delta <= 0.0 {
+    return 0.0;
+  }
+
+  cpu_delta / system_delta * online_cpus * 100.0
+}
+
+// Same calculation as `docker stats`, the page cache is not counted as used memory
+// cgroup v1 reports it as `total_inactive_file`, cgroup v2 as `inactive_file`
+fn memory_usage_without_cache(memory: &ContainerMemoryStats) -> u64 {
+  let usage = memory.usage.unwrap_or_default();
+  let cache = memory
+    .stats
+    .as_ref()
+    .and_then(|stats| {
+      stats
+        .get("total_inactive_file")
+        .or_else(|| stats.get("inactive_file"))
+    })
+    .copied()
+    .unwrap_or_default();
+
+  usage.saturating_sub(cache)
+}
+
+fn percentage(value: f64, total: f64) -> f64 {
+  if total <= 0.0 {
+    return 0.0;
+  }
+
+  value / total * 100.0
+}
+
+#[cfg(test)]
+mod tests {
+  use std::collections::HashMap;
+
+  use bollard::models::{
+    ContainerBlkioStatEntry, ContainerBlkioStats, ContainerCpuStats, ContainerCpuUsage,
+    ContainerNetworkStats, ContainerPidsStats,
+  };
+
+  use super::*;
+
+  #[test]
+  fn test_deployment_stats_from_container_stats() {
+    let container_stats = ContainerStatsResponse {
+      cpu_stats: Some(ContainerCpuStats {
+        cpu_usage: Some(ContainerCpuUsage {
+          total_usage: Some(400),
+          ..Default::default()
+        }),
+        system_cpu_usage: Some(2000),
+        online_cpus: Some(4),
+        ..Default::default()
+      }),
+      precpu_stats: Some(ContainerCpuStats {
+        cpu_usage: Some(ContainerCpuUsage {
+          total_usage: Some(200),
+          ..Default::default()
+        }),
+        system_cpu_usage: Some(1000),
+        online_cpus: Some(4),
+        ..Default::default()
+      }),
+      memory_stats: Some(ContainerMemoryStats {
+        usage: Some(600),
+        limit: Some(1000),
+        stats: Some(HashMap::from([("inactive_file".to_string(), 100)])),
+        ..Default::default()
+      }),
+      networks: Some(HashMap::from([
+        (
+          "eth0".to_string(),
+          ContainerNetworkStats {
+            rx_bytes: Some(10),
+            tx_bytes: Some(20),
+            ..Default::default()
+          },
+        ),
+        (
+          "eth1".to_string(),
+          ContainerNetworkStats {
+            rx_bytes: Some(1),
+            tx_bytes: Some(2),
+            ..Default::default()
+          },
+        ),
+      ])),
+      blkio_stats: Some(ContainerBlkioStats {
+        io_service_bytes_recursive: Some(vec![
+          ContainerBlkioStatEntry {
+            op: Some("read".to_string()),
+            value: Some(100),
+            ..Default::default()
+          },
+          ContainerBlkioStatEntry {
+            op: Some("Write".to_string()),
+            value: Some(50),
+            ..Default::default()
+          },
+          ContainerBlkioStatEntry {
+            op: Some("total".to_string()),
+            value: Some(150),
+            ..Default::default()
+          },
+        ]),
+        ..Default::default()
+      }),
+      pids_stats: Some(ContainerPidsStats {
+        current: Some(42),
+        ..Default::default()
+      }),
+      ..Default::default()
+    };
+
+    let stats: DeploymentStats = container_stats.into();
+
+    assert_eq!(stats.cpu_percentage, 80.0);
+    assert_eq!(stats.memory_usage, 500);
+    assert_eq!(stats.memory_limit, 1000);
+    assert_eq!(stats.memory_percentage, 50.0);
+    assert!(!stats.oom_killed);
+    assert_eq!(stats.network_rx_bytes, 11);
+    assert_eq!(stats.network_tx_bytes, 22);
+    assert_eq!(stats.block_read_bytes, 100);
+    assert_eq!(stats.block_write_bytes, 50);
+    assert_eq!(stats.pids, 42);
+    assert_eq!(stats.data_disk_usage, None);
+  }
+
+  #[test]
+  fn test_deployment_stats_from_empty_container_stats() {
+    let stats: DeploymentStats = ContainerStatsResponse::default().into();
+
+    assert_eq!(stats.cpu_percentage, 0.0);
+    assert_eq!(stats.memory_usage, 0);
+    assert_eq!(stats.memory_limit, 0);
+    assert_eq!(stats.memory_percentage, 0.0);
+    assert_eq!(stats.network_rx_bytes, 0);
+    assert_eq!(stats.network_tx_bytes, 0);
+    assert_eq!(stats.block_read_bytes, 0);
+    assert_eq!(stats.block_write_bytes, 0);
+    assert_eq!(stats.pids, 0);
+  }
+}
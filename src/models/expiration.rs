@@ -0,0 +1,268 @@
+use std::collections::HashMap;
+
+use napi_derive::napi;
+
+use crate::models::create_deployment::CreateDeploymentOptions;
+use crate::models::list_deployments::{CreationSource, Deployment};
+
+// Labels recording when a deployment expires (seconds since the Unix epoch) and which process
+// created it, they are read by `reapExpiredDeployments`
+pub(crate) const EXPIRES_AT_LABEL: &str = "mongodb-atlas-local.expires-at";
+pub(crate) const OWNER_PID_LABEL: &str = "mongodb-atlas-local.owner-pid";
+pub(crate) const OWNER_HOSTNAME_LABEL: &str = "mongodb-atlas-local.owner-hostname";
+
+#[napi(object)]
+pub struct ReapOptions {
+  pub dry_run: Option<bool>,
+  pub creation_source: Option<CreationSource>,
+}
+
+#[napi(object)]
+#[derive(PartialEq, Debug)]
+pub struct ReapedDeployment {
+  pub name: String,
+  pub reason: ReapReason,
+}
+
+#[napi(string_enum)]
+#[derive(PartialEq, Debug)]
+pub enum ReapReason {
+  Expired,
+  OwnerExited,
+}
+
+#[derive(PartialEq, Debug)]
+pub(crate) struct Owner {
+  pub pid: u32,
+  pub hostname: String,
+}
+
+impl Owner {
+  pub(crate) fn current() -> Self {
+    Self {
+      pid: std::process::id(),
+      hostname: gethostname::gethostname().to_string_lossy().into_owned(),
+    }
+  }
+
+  // Processes on other hosts can't be checked, they are assumed to be running
+  pub(crate) fn is_running(&self, hostname: &str) -> bool {
+    self.hostname != hostname || is_process_running(self.pid)
+  }
+}
+
+#[cfg(unix)]
+fn is_process_running(pid: u32) -> bool {
+  let Ok(pid) = libc::pid_t::try_from(pid) else {
+    return true;
+  };
+
+  // Signal 0 only checks whether the process exists, EPERM means it exists but belongs to
+  // another user
+  let result = unsafe { libc::kill(pid, 0) };
+  result == 0 || std::io::Error::last_os_error().raw_os_error() != Some(libc::ESRCH)
+}
+
+#[cfg(not(unix))]
+fn is_process_running(_pid: u32) -> bool {
+  true
+}
+
+// Labels for the expiration options of a new deployment, `now` is in seconds since the Unix epoch
+// When both `ttl` and `expiresAt` are set the earliest expiration wins
+pub(crate) fn expiration_labels(
+  options: &CreateDeploymentOptions,
+  owner: &Owner,
+  now: i64,
+) -> HashMap<String, String> {
+  let mut labels = HashMap::new();
+
+  let expires_at = [options.ttl.map(|ttl| now + ttl as i64), options.expires_at]
+    .into_iter()
+    .flatten()
+    .min();
+  if let Some(expires_at) = expires_at {
+    labels.insert(EXPIRES_AT_LABEL.to_string(), expires_at.to_string());
+  }
+
+  if options.remove_when_owner_exits.unwrap_or_default() {
+    labels.insert(OWNER_PID_LABEL.to_string(), owner.pid.to_string());
+    labels.insert(OWNER_HOSTNAME_LABEL.to_string(), owner.hostname.clone());
+  }
+
+  labels
+}
+
+pub(crate) fn apply_expiration_labels(
+  deployment: &mut Deployment,
+  labels: &HashMap<String, String>,
+) {
+  deployment.expires_at = labels
+    .get(EXPIRES_AT_LABEL)
+    .and_then(|value| value.parse().ok());
+  deployment.owner_pid = labels
+    .get(OWNER_PID_LABEL)
+    .and_then(|value| value.parse().ok());
+  deployment.owner_hostname = labels.get(OWNER_HOSTNAME_LABEL).cloned();
+}
+
+// Why a deployment should be removed, if at all
+// `is_running` tells whether the owner of the deployment is still running
+pub(crate) fn reap_reason(
+  deployment: &Deployment,
+  options: &ReapOptions,
+  now: i64,
+  is_running: impl Fn(&Owner) -> bool,
+) -> Option<ReapReason> {
+  if options.creation_source.is_some() && deployment.creation_source != options.creation_source {
+    return None;
+  }
+
+  if deployment
+    .expires_at
+    .is_some_and(|expires_at| expires_at <= now)
+  {
+    return Some(ReapReason::Expired);
+  }
+
+  let owner = match (deployment.owner_pid, &deployment.owner_hostname) {
+    (Some(pid), Some(hostname)) => Owner {
+      pid,
+      hostname: hostname.clone(),
+    },
+    _ => return None,
+  };
+  (!is_running(&owner)).then_some(ReapReason::OwnerExited)
+}
+
+#[cfg(test)]
+mod tests {
+  use crate::models::list_deployments::{test_deployment, CreationSourceType};
+
+  use super::*;
+
+  fn owner() -> Owner {
+    Owner {
+      pid: 4242,
+      hostname: "test_host".to_string(),
+    }
+  }
+
+  fn deployment() -> Deployment {
+    test_deployment("test_deployment")
+  }
+
+  fn reap_options() -> ReapOptions {
+    ReapOptions {
+      dry_run: None,
+      creation_source: None,
+    }
+  }
+
+  #[test]
+  fn test_expiration_labels() {
+    let options = CreateDeploymentOptions {
+      ttl: Some(3600),
+      expires_at: Some(2000),
+      remove_when_owner_exits: Some(true),
+      ..Default::default()
+    };
+
+    let labels = expiration_labels(&options, &owner(), 1000);
+
+    assert_eq!(
+      labels,
+      HashMap::from([
+        (EXPIRES_AT_LABEL.to_string(), "2000".to_string()),
+        (OWNER_PID_LABEL.to_string(), "4242".to_string()),
+        (OWNER_HOSTNAME_LABEL.to_string(), "test_host".to_string()),
+      ])
+    );
+  }
+
+  #[test]
+  fn test_expiration_labels_without_options() {
+    let labels = expiration_labels(&CreateDeploymentOptions::default(), &owner(), 1000);
+
+    assert!(labels.is_empty());
+  }
+
+  #[test]
+  fn test_apply_expiration_labels() {
+    let mut deployment = deployment();
+    apply_expiration_labels(
+      &mut deployment,
+      &HashMap::from([
+        (EXPIRES_AT_LABEL.to_string(), "4600".to_string()),
+        (OWNER_PID_LABEL.to_string(), "4242".to_string()),
+        (OWNER_HOSTNAME_LABEL.to_string(), "test_host".to_string()),
+      ]),
+    );
+
+    assert_eq!(deployment.expires_at, Some(4600));
+    assert_eq!(deployment.owner_pid, Some(4242));
+    assert_eq!(deployment.owner_hostname, Some("test_host".to_string()));
+  }
+
+  #[test]
+  fn test_reap_reason_expired() {
+    let mut deployment = deployment();
+    deployment.expires_at = Some(1000);
+
+    assert_eq!(
+      reap_reason(&deployment, &reap_options(), 1000, |_| true),
+      Some(ReapReason::Expired)
+    );
+    assert_eq!(
+      reap_reason(&deployment, &reap_options(), 999, |_| true),
+      None
+    );
+  }
+
+  #[test]
+  fn test_reap_reason_owner_exited() {
+    let mut deployment = deployment();
+    deployment.owner_pid = Some(4242);
+    deployment.owner_hostname = Some("test_host".to_string());
+
+    assert_eq!(
+      reap_reason(&deployment, &reap_options(), 1000, |owner| {
+        *owner != self::owner()
+      }),
+      Some(ReapReason::OwnerExited)
+    );
+    assert_eq!(
+      reap_reason(&deployment, &reap_options(), 1000, |_| true),
+      None
+    );
+  }
+
+  #[test]
+  fn test_reap_reason_filters_creation_source() {
+    let mut deployment = deployment();
+    deployment.expires_at = Some(1000);
+    deployment.creation_source = Some(CreationSource {
+      source_type: CreationSourceType::AtlasCLI,
+      source: "ATLASCLI".to_string(),
+    });
+    let options = ReapOptions {
+      dry_run: None,
+      creation_source: Some(CreationSource {
+        source_type: CreationSourceType::MCPServer,
+        source: "MCPSERVER".to_string(),
+      }),
+    };
+
+    assert_eq!(reap_reason(&deployment, &options, 1000, |_| true), None);
+  }
+
+  #[test]
+  fn test_owner_on_other_host_is_running() {
+    let owner = Owner {
+      pid: u32::MAX,
+      hostname: "other_host".to_string(),
+    };
+
+    assert!(owner.is_running("test_host"));
+  }
+}
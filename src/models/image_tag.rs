@@ -0,0 +1,206 @@
+use std::cmp::Ordering;
+
+use anyhow::Result;
+use atlas_local::models::{ImageTag, MongoDBVersion};
+use napi_derive::napi;
+use semver::{Version, VersionReq};
+
+#[napi(object)]
+#[derive(PartialEq, Debug)]
+pub struct ParsedImageTag {
+  pub kind: ImageTagKind,
+  pub major: Option<u32>,
+  pub minor: Option<u32>,
+  pub patch: Option<u32>,
+}
+
+#[napi(string_enum = "lowercase")]
+#[derive(PartialEq, Debug)]
+pub enum ImageTagKind {
+  Semver,
+  Latest,
+  Preview,
+}
+
+pub(crate) fn parse_image_tag(tag: &str) -> Result<ParsedImageTag> {
+  let image_tag = ImageTag::try_from(tag).map_err(anyhow::Error::msg)?;
+
+  let parsed = match image_tag {
+    // Timestamped tags like `8.2.4-20260217T084055Z` are builds of the same version
+    ImageTag::Semver(version) | ImageTag::SemverTimestamp(version, _) => {
+      ParsedImageTag::from_version(version)
+    }
+    ImageTag::Latest => ParsedImageTag::without_version(ImageTagKind::Latest),
+    ImageTag::Preview => ParsedImageTag::without_version(ImageTagKind::Preview),
+  };
+
+  Ok(parsed)
+}
+
+impl ParsedImageTag {
+  fn from_version(version: MongoDBVersion) -> Self {
+    match version {
+      MongoDBVersion::Major(version) => ParsedImageTag {
+        kind: ImageTagKind::Semver,
+        major: Some(version.major as u32),
+        minor: None,
+        patch: None,
+      },
+      MongoDBVersion::MajorMinor(version) => ParsedImageTag {
+        kind: ImageTagKind::Semver,
+        major: Some(version.major as u32),
+        minor: Some(version.minor as u32),
+        patch: None,
+      },
+      MongoDBVersion::MajorMinorPatch(version) => ParsedImageTag {
+        kind: ImageTagKind::Semver,
+        major: Some(version.major as u32),
+        minor: Some(version.minor as u32),
+        patch: Some(version.patch as u32),
+      },
+    }
+  }
+
+  fn without_version(kind: ImageTagKind) -> Self {
+    ParsedImageTag {
+      kind,
+      major: None,
+      minor: None,
+      patch: None,
+    }
+  }
+}
+
+// A partial version like `8` stands for a range of versions, it can't be ordered against a
+// single version
+fn parse_version(version: &str) -> Result<Version> {
+  match parse_image_tag(version)? {
+    ParsedImageTag {
+      kind: ImageTagKind::Semver,
+      major: Some(major),
+      minor: Some(minor),
+      patch: Some(patch),
+    } => Ok(Version::new(major as u64, minor as u64, patch as u64)),
+    ParsedImageTag {
+      kind: ImageTagKind::Semver,
+      ..
+    } => anyhow::bail!("{version} is not a full MongoDB version (major.minor.patch)"),
+    _ => anyhow::bail!("{version} is not a MongoDB version"),
+  }
+}
+
+pub(crate) fn compare_versions(a: &str, b: &str) -> Result<i32> {
+  let ordering = match parse_version(a)?.cmp(&parse_version(b)?) {
+    Ordering::Less => -1,
+    Ordering::Equal => 0,
+    Ordering::Greater => 1,
+  };
+
+  Ok(ordering)
+}
+
+pub(crate) fn satisfies_version(version: &str, range: &str) -> Result<bool> {
+  let range = VersionReq::parse(range)?;
+
+  Ok(range.matches(&parse_version(version)?))
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  #[test]
+  fn test_parse_image_tag_major_minor_patch() {
+    assert_eq!(
+      parse_image_tag("8.0.4").unwrap(),
+      ParsedImageTag {
+        kind: ImageTagKind::Semver,
+        major: Some(8),
+        minor: Some(0),
+        patch: Some(4),
+      }
+    );
+  }
+
+  #[test]
+  fn test_parse_image_tag_major_minor() {
+    assert_eq!(
+      parse_image_tag("7.0").unwrap(),
+      ParsedImageTag {
+        kind: ImageTagKind::Semver,
+        major: Some(7),
+        minor: Some(0),
+        patch: None,
+      }
+    );
+  }
+
+  #[test]
+  fn test_parse_image_tag_major() {
+    assert_eq!(
+      parse_image_tag("8").unwrap(),
+      ParsedImageTag {
+        kind: ImageTagKind::Semver,
+        major: Some(8),
+        minor: None,
+        patch: None,
+      }
+    );
+  }
+
+  #[test]
+  fn test_parse_image_tag_semver_timestamp() {
+    assert_eq!(
+      parse_image_tag("8.2.4-20260217T084055Z").unwrap(),
+      ParsedImageTag {
+        kind: ImageTagKind::Semver,
+        major: Some(8),
+        minor: Some(2),
+        patch: Some(4),
+      }
+    );
+  }
+
+  #[test]
+  fn test_parse_image_tag_latest() {
+    assert_eq!(
+      parse_image_tag("latest").unwrap().kind,
+      ImageTagKind::Latest
+    );
+  }
+
+  #[test]
+  fn test_parse_image_tag_preview() {
+    assert_eq!(
+      parse_image_tag("preview").unwrap().kind,
+      ImageTagKind::Preview
+    );
+  }
+
+  #[test]
+  fn test_parse_image_tag_invalid() {
+    assert!(parse_image_tag("not a tag!").is_err());
+  }
+
+  #[test]
+  fn test_compare_versions() {
+    assert_eq!(compare_versions("8.0.4", "8.0.10").unwrap(), -1);
+    assert_eq!(compare_versions("8.1.0", "8.0.12").unwrap(), 1);
+    assert!(compare_versions("8", "8.0.0").is_err());
+    assert!(compare_versions("latest", "8.0.0").is_err());
+    assert_eq!(
+      compare_versions("8.2.4-20260217T084055Z", "8.2.4").unwrap(),
+      0
+    );
+  }
+
+  #[test]
+  fn test_satisfies_version() {
+    assert!(satisfies_version("8.0.4", ">=8.0.0").unwrap());
+    assert!(satisfies_version("7.0.12", "^7.0").unwrap());
+    assert!(!satisfies_version("7.0.12", ">=8").unwrap());
+    assert!(satisfies_version("8.0.4", "not a range").is_err());
+    assert!(satisfies_version("preview", ">=8").is_err());
+    assert!(satisfies_version("8.0", ">=8").is_err());
+  }
+}
@@ -0,0 +1,385 @@
+use std::collections::HashMap;
+
+use bollard::models::ImageSummary;
+use napi_derive::napi;
+
+#[napi(object)]
+#[derive(Clone, PartialEq, Debug)]
+pub struct Image {
+  // Identifiers
+  pub id: String,
+  pub repository: String,
+  pub tag: Option<String>,
+  pub digest: Option<String>,
+
+  // Image details
+  pub size: i64,
+  pub created: i64,
+  pub mongodb_version: Option<String>,
+
+  // Deployments using this image
+  pub used_by: Vec<String>,
+}
+
+#[napi(object)]
+pub struct ResolvedImageTag {
+  pub tag: String,
+  pub digest: String,
+  // Only known when the image has been pulled
+  pub image_id: Option<String>,
+  // From the image when it has been pulled, otherwise only known for full version tags
+  pub mongodb_version: Option<String>,
+}
+
+#[napi(object)]
+pub struct RemoveImageOptions {
+  pub force: Option<bool>,
+}
+
+#[napi(object)]
+pub struct PruneImagesOptions {
+  pub keep_latest: Option<u32>,
+  pub older_than: Option<u32>,
+}
+
+#[napi(object)]
+pub struct PruneImagesResult {
+  pub removed: Vec<Image>,
+  pub reclaimed_bytes: i64,
+}
+
+impl Image {
+  // Docker lists an image once with all its tags
+  // This returns one entry per tag of the given repository
+  pub(crate) fn from_summary(
+    source: ImageSummary,
+    repository: &str,
+    used_by: &HashMap<String, Vec<String>>,
+  ) -> Vec<Image> {
+    let digest = source
+      .repo_digests
+      .iter()
+      .find_map(|repo_digest| digest_from_repo_digest(repo_digest, repository));
+    let mut tags: Vec<Option<String>> = source
+      .repo_tags
+      .iter()
+      .filter_map(|repo_tag| {
+        repo_tag
+          .strip_prefix(repository)
+          .and_then(|rest| rest.strip_prefix(':'))
+          .map(|tag| Some(tag.to_string()))
+      })
+      .collect();
+
+    // Untagged images are only listed when they still have a digest in the repository
+    if tags.is_empty() && digest.is_some() {
+      tags.push(None);
+    }
+
+    tags
+      .into_iter()
+      .map(|tag| Image {
+        id: source.id.clone(),
+        repository: repository.to_string(),
+        tag,
+        digest: digest.clone(),
+        size: source.size,
+        created: source.created,
+        mongodb_version: source.labels.get("version").cloned(),
+        used_by: used_by.get(&source.id).cloned().unwrap_or_default(),
+      })
+      .collect()
+  }
+
+  // Reference that uniquely points to this entry, the tag when present or the digest otherwise
+  pub(crate) fn reference(&self) -> String {
+    match (&self.tag, &self.digest) {
+      (Some(tag), _) => format!("{}:{}", self.repository, tag),
+      (None, Some(digest)) => format!("{}@{}", self.repository, digest),
+      (None, None) => self.id.clone(),
+    }
+  }
+}
+
+// Turns a tag (`8.0.4`), digest (`sha256:...`) or full reference into an image reference
+pub(crate) fn image_reference(repository: &str, tag_or_digest: &str) -> String {
+  if tag_or_digest.starts_with("sha256:") {
+    format!("{repository}@{tag_or_digest}")
+  } else if tag_or_digest.contains('/') {
+    tag_or_digest.to_string()
+  } else {
+    format!("{repository}:{tag_or_digest}")
+  }
+}
+
+// `mongodb/mongodb-atlas-local@sha256:...` -> `sha256:...`
+pub(crate) fn digest_from_repo_digest(repo_digest: &str, repository: &str) -> Option<String> {
+  repo_digest
+    .strip_prefix(repository)
+    .and_then(|rest| rest.strip_prefix('@'))
+    .map(str::to_string)
+}
+
+// Repository a pinned image is tagged in, one per digest
+// Retagging the shared `repository:tag` would switch every other user of that tag to the pinned
+// image, atlas-local only accepts version tags so the digest goes into the repository name
+pub(crate) fn pinned_repository(repository: &str, digest: &str) -> String {
+  let hash = digest.strip_prefix("sha256:").unwrap_or(digest);
+  format!("{repository}-pinned-{}", &hash[..hash.len().min(16)])
+}
+
+// The repository a pinned repository was created from, other repositories are returned as is
+pub(crate) fn unpinned_repository(repository: &str) -> &str {
+  match repository.rsplit_once("-pinned-") {
+    Some((unpinned, hash)) if !hash.is_empty() && hash.chars().all(|c| c.is_ascii_hexdigit()) => {
+      unpinned
+    }
+    _ => repository,
+  }
+}
+
+// `mongodb/mongodb-atlas-local:8.0.4` -> `8.0.4`, a registry port is not a tag
+pub(crate) fn tag_from_reference(reference: &str) -> Option<String> {
+  let reference = reference.split('@').next().unwrap_or(reference);
+  let (name, tag) = reference.rsplit_once(':')?;
+  if tag.contains('/') || name.is_empty() {
+    return None;
+  }
+
+  Some(tag.to_string())
+}
+
+// Selects the images that can be removed, images in use are never removed
+// `keep_latest` keeps the most recently created images
+// `older_than` (seconds) only removes images created before `now - older_than`
+pub(crate) fn prunable_images(
+  images: &[Image],
+  options: &PruneImagesOptions,
+  now: i64,
+) -> Vec<Image> {
+  let mut image_ids: Vec<(&str, i64)> = Vec::new();
+  for image in images {
+    if !image_ids.iter().any(|(id, _)| *id == image.id) {
+      image_ids.push((&image.id, image.created));
+    }
+  }
+  image_ids.sort_by(|(_, a), (_, b)| b.cmp(a));
+
+  let kept: Vec<&str> = image_ids
+    .iter()
+    .take(options.keep_latest.unwrap_or_default() as usize)
+    .map(|(id, _)| *id)
+    .collect();
+
+  images
+    .iter()
+    .filter(|image| image.used_by.is_empty())
+    .filter(|image| !kept.contains(&image.id.as_str()))
+    .filter(|image| {
+      options
+        .older_than
+        .map(|older_than| image.created < now - older_than as i64)
+        .unwrap_or(true)
+    })
+    .cloned()
+    .collect()
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  const REPOSITORY: &str = "mongodb/mongodb-atlas-local";
+
+  fn image(id: &str, tag: &str, created: i64, used_by: Vec<String>) -> Image {
+    Image {
+      id: id.to_string(),
+      repository: REPOSITORY.to_string(),
+      tag: Some(tag.to_string()),
+      digest: None,
+      size: 100,
+      created,
+      mongodb_version: None,
+      used_by,
+    }
+  }
+
+  #[test]
+  fn test_images_from_image_summary() {
+    let summary = ImageSummary {
+      id: "sha256:image".to_string(),
+      repo_tags: vec![
+        "mongodb/mongodb-atlas-local:8.0.4".to_string(),
+        "mongodb/mongodb-atlas-local:latest".to_string(),
+        "other/image:latest".to_string(),
+      ],
+      repo_digests: vec!["mongodb/mongodb-atlas-local@sha256:digest".to_string()],
+      created: 1700000000,
+      size: 1024,
+      labels: HashMap::from([("version".to_string(), "8.0.4".to_string())]),
+      ..Default::default()
+    };
+    let used_by = HashMap::from([(
+      "sha256:image".to_string(),
+      vec!["test_deployment".to_string()],
+    )]);
+
+    let images = Image::from_summary(summary, REPOSITORY, &used_by);
+
+    assert_eq!(images.len(), 2);
+    assert_eq!(images[0].id, "sha256:image");
+    assert_eq!(images[0].repository, REPOSITORY);
+    assert_eq!(images[0].tag, Some("8.0.4".to_string()));
+    assert_eq!(images[0].digest, Some("sha256:digest".to_string()));
+    assert_eq!(images[0].size, 1024);
+    assert_eq!(images[0].created, 1700000000);
+    assert_eq!(images[0].mongodb_version, Some("8.0.4".to_string()));
+    assert_eq!(images[0].used_by, vec!["test_deployment".to_string()]);
+    assert_eq!(images[1].tag, Some("latest".to_string()));
+  }
+
+  #[test]
+  fn test_images_from_untagged_image_summary() {
+    let summary = ImageSummary {
+      id: "sha256:image".to_string(),
+      repo_digests: vec!["mongodb/mongodb-atlas-local@sha256:digest".to_string()],
+      ..Default::default()
+    };
+
+    let images = Image::from_summary(summary, REPOSITORY, &HashMap::new());
+
+    assert_eq!(images.len(), 1);
+    assert_eq!(images[0].tag, None);
+    assert_eq!(
+      images[0].reference(),
+      "mongodb/mongodb-atlas-local@sha256:digest"
+    );
+  }
+
+  #[test]
+  fn test_images_from_other_repository_image_summary() {
+    let summary = ImageSummary {
+      id: "sha256:image".to_string(),
+      repo_tags: vec!["other/image:latest".to_string()],
+      ..Default::default()
+    };
+
+    assert!(Image::from_summary(summary, REPOSITORY, &HashMap::new()).is_empty());
+  }
+
+  #[test]
+  fn test_image_reference() {
+    assert_eq!(
+      image_reference(REPOSITORY, "8.0.4"),
+      "mongodb/mongodb-atlas-local:8.0.4"
+    );
+    assert_eq!(
+      image_reference(REPOSITORY, "sha256:digest"),
+      "mongodb/mongodb-atlas-local@sha256:digest"
+    );
+    assert_eq!(
+      image_reference(REPOSITORY, "mongodb/mongodb-atlas-local:preview"),
+      "mongodb/mongodb-atlas-local:preview"
+    );
+  }
+
+  #[test]
+  fn test_digest_from_repo_digest() {
+    assert_eq!(
+      digest_from_repo_digest("mongodb/mongodb-atlas-local@sha256:digest", REPOSITORY),
+      Some("sha256:digest".to_string())
+    );
+    assert_eq!(
+      digest_from_repo_digest("other/image@sha256:digest", REPOSITORY),
+      None
+    );
+  }
+
+  #[test]
+  fn test_pinned_repository() {
+    assert_eq!(
+      pinned_repository(REPOSITORY, "sha256:0123456789abcdef0123456789abcdef"),
+      "mongodb/mongodb-atlas-local-pinned-0123456789abcdef"
+    );
+  }
+
+  #[test]
+  fn test_unpinned_repository() {
+    assert_eq!(
+      unpinned_repository("mongodb/mongodb-atlas-local-pinned-0123456789abcdef"),
+      REPOSITORY
+    );
+    assert_eq!(unpinned_repository(REPOSITORY), REPOSITORY);
+    assert_eq!(
+      unpinned_repository("registry.local/atlas-pinned-images"),
+      "registry.local/atlas-pinned-images"
+    );
+  }
+
+  #[test]
+  fn test_tag_from_reference() {
+    assert_eq!(
+      tag_from_reference("mongodb/mongodb-atlas-local:8.0.4"),
+      Some("8.0.4".to_string())
+    );
+    assert_eq!(
+      tag_from_reference("localhost:5000/mongodb-atlas-local:latest"),
+      Some("latest".to_string())
+    );
+    assert_eq!(
+      tag_from_reference("localhost:5000/mongodb-atlas-local"),
+      None
+    );
+    assert_eq!(tag_from_reference("mongodb/mongodb-atlas-local"), None);
+  }
+
+  #[test]
+  fn test_prunable_images_skips_images_in_use() {
+    let images = vec![
+      image("a", "8.0.4", 300, vec!["test_deployment".to_string()]),
+      image("b", "8.0.3", 200, vec![]),
+    ];
+    let options = PruneImagesOptions {
+      keep_latest: None,
+      older_than: None,
+    };
+
+    let prunable = prunable_images(&images, &options, 1000);
+
+    assert_eq!(prunable, vec![images[1].clone()]);
+  }
+
+  #[test]
+  fn test_prunable_images_keep_latest() {
+    let images = vec![
+      image("a", "8.0.4", 300, vec![]),
+      image("a", "latest", 300, vec![]),
+      image("b", "8.0.3", 200, vec![]),
+      image("c", "8.0.2", 100, vec![]),
+    ];
+    let options = PruneImagesOptions {
+      keep_latest: Some(2),
+      older_than: None,
+    };
+
+    let prunable = prunable_images(&images, &options, 1000);
+
+    assert_eq!(prunable, vec![images[3].clone()]);
+  }
+
+  #[test]
+  fn test_prunable_images_older_than() {
+    let images = vec![
+      image("a", "8.0.4", 900, vec![]),
+      image("b", "8.0.3", 100, vec![]),
+    ];
+    let options = PruneImagesOptions {
+      keep_latest: None,
+      older_than: Some(500),
+    };
+
+    let prunable = prunable_images(&images, &options, 1000);
+
+    assert_eq!(prunable, vec![images[1].clone()]);
+  }
+}
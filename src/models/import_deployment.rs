@@ -0,0 +1,915 @@
+use std::collections::HashMap;
+
+use anyhow::{Context, Result};
+use napi_derive::napi;
+use serde_yaml::Value;
+
+use crate::docker::{
+  ATLAS_LOCAL_IMAGE, CONFIG_DIRECTORY, DATA_DIRECTORY, INIT_DIRECTORY, LOCAL_DEPLOYMENT_LABEL,
+  MONGODB_PORT, MONGOT_PORT,
+};
+use crate::models::create_deployment::CreateDeploymentOptions;
+use crate::models::images::unpinned_repository;
+use crate::models::list_deployments::{BindingType, CreationSource, MongoDBPortBinding};
+use crate::models::replica_set::OPLOG_SIZE_LABEL;
+use crate::models::secrets::{
+  secret_file, SecretSource, ROOT_PASSWORD_SECRET, ROOT_PASSWORD_SOURCE_LABEL,
+};
+use crate::models::server_parameters::{
+  mongod_config_file, MONGOD_CONFIG_LABEL, SET_PARAMETERS_LABEL,
+};
+
+#[napi(object)]
+#[derive(Debug)]
+pub struct ImportedDeployment {
+  pub options: CreateDeploymentOptions,
+  pub unsupported_keys: Vec<String>,
+}
+
+// Container definition shared by docker compose services and docker run commands
+#[derive(Default)]
+struct ContainerDefinition {
+  name: Option<String>,
+  image: Option<String>,
+  environment: Vec<(String, String)>,
+  ports: Vec<String>,
+  volumes: Vec<String>,
+  labels: Vec<(String, String)>,
+  command: Vec<String>,
+  unsupported_keys: Vec<String>,
+}
+
+pub(crate) fn parse_compose_service(
+  yaml: &str,
+  service_name: Option<&str>,
+) -> Result<ImportedDeployment> {
+  let compose: Value = serde_yaml::from_str(yaml).context("parse docker compose file")?;
+  let services = compose
+    .get("services")
+    .and_then(Value::as_mapping)
+    .context("docker compose file has no services")?;
+
+  let (name, service) = match service_name {
+    Some(service_name) => (
+      service_name,
+      services
+        .get(service_name)
+        .with_context(|| format!("service {service_name} not found"))?,
+    ),
+    None => services
+      .iter()
+      .find(|(_, service)| {
+        service
+          .get("image")
+          .and_then(Value::as_str)
+          .is_some_and(is_atlas_local_image)
+      })
+      .and_then(|(name, service)| name.as_str().map(|name| (name, service)))
+      .context("no service uses the atlas-local image")?,
+  };
+
+  let mut definition = ContainerDefinition {
+    name: Some(name.to_string()),
+    ..Default::default()
+  };
+  for (key, value) in service.as_mapping().context("service is not a mapping")? {
+    let key = key.as_str().unwrap_or_default();
+    match key {
+      "image" => definition.image = scalar(value),
+      "container_name" => definition.name = scalar(value),
+      "environment" => definition.environment = key_values(value),
+      "labels" => definition.labels = key_values(value),
+      "command" => match command(value) {
+        Some(command) => definition.command = command,
+        None => definition.unsupported_keys.push(key.to_string()),
+      },
+      "ports" => definition.ports = short_syntax(key, value, &mut definition.unsupported_keys),
+      "volumes" => definition.volumes = short_syntax(key, value, &mut definition.unsupported_keys),
+      _ => definition.unsupported_keys.push(key.to_string()),
+    }
+  }
+
+  definition.try_into()
+}
+
+pub(crate) fn parse_docker_run(command: &str) -> Result<ImportedDeployment> {
+  let args = shell_words::split(command).context("parse docker run command")?;
+  let mut args = args.into_iter().peekable();
+
+  // `docker run`, `docker container run` or just the arguments
+  if args.peek().map(String::as_str) == Some("docker") {
+    args.next();
+  }
+  if args.peek().map(String::as_str) == Some("container") {
+    args.next();
+  }
+  if args.peek().map(String::as_str) == Some("run") {
+    args.next();
+  }
+
+  let mut definition = ContainerDefinition::default();
+  while let Some(arg) = args.next() {
+    if !arg.starts_with('-') {
+      definition.image = Some(arg);
+      break;
+    }
+
+    let (flag, inline_value) = match arg.split_once('=') {
+      Some((flag, value)) => (flag.to_string(), Some(value.to_string())),
+      None => (arg, None),
+    };
+    if BOOLEAN_FLAGS.contains(&flag.as_str()) {
+      if !IGNORED_FLAGS.contains(&flag.as_str()) {
+        definition.unsupported_keys.push(flag);
+      }
+      continue;
+    }
+
+    // Without a value given inline, an unknown flag could be a boolean one followed by the image
+    if inline_value.is_none() && !VALUE_FLAGS.contains(&flag.as_str()) {
+      anyhow::bail!("unknown docker run flag {flag}");
+    }
+    let value = inline_value
+      .or_else(|| args.next())
+      .with_context(|| format!("{flag} needs a value"))?;
+    match flag.as_str() {
+      "--name" => definition.name = Some(value),
+      "-e" | "--env" => match value.split_once('=') {
+        Some((key, value)) => definition
+          .environment
+          .push((key.to_string(), value.to_string())),
+        None => definition.unsupported_keys.push(format!("{flag} {value}")),
+      },
+      "-p" | "--publish" => definition.ports.push(value),
+      "-v" | "--volume" => definition.volumes.push(value),
+      "-l" | "--label" => {
+        let (key, value) = value.split_once('=').unwrap_or((&value, ""));
+        definition.labels.push((key.to_string(), value.to_string()));
+      }
+      _ => definition.unsupported_keys.push(flag),
+    }
+  }
+  definition.command = args.collect();
+
+  definition.try_into()
+}
+
+// Flags of `docker run` that don't take a value
+const BOOLEAN_FLAGS: &[&str] = &[
+  "-d",
+  "--detach",
+  "-i",
+  "--interactive",
+  "-t",
+  "--tty",
+  "-it",
+  "-ti",
+  "--rm",
+  "--init",
+  "--privileged",
+  "-P",
+  "--publish-all",
+  "--read-only",
+  "--no-healthcheck",
+  "--oom-kill-disable",
+  "-q",
+  "--quiet",
+];
+
+// Flags of `docker run` that take a value
+const VALUE_FLAGS: &[&str] = &[
+  "--name",
+  "-e",
+  "--env",
+  "--env-file",
+  "-p",
+  "--publish",
+  "-v",
+  "--volume",
+  "--mount",
+  "--volumes-from",
+  "--tmpfs",
+  "-m",
+  "--memory",
+  "--memory-swap",
+  "--memory-reservation",
+  "--cpus",
+  "--cpu-shares",
+  "--cpuset-cpus",
+  "--shm-size",
+  "--ulimit",
+  "--network",
+  "--net",
+  "--network-alias",
+  "--ip",
+  "--ip6",
+  "--dns",
+  "--add-host",
+  "--link",
+  "-h",
+  "--hostname",
+  "-l",
+  "--label",
+  "--label-file",
+  "--restart",
+  "-u",
+  "--user",
+  "-w",
+  "--workdir",
+  "--entrypoint",
+  "--platform",
+  "--pull",
+  "--health-cmd",
+  "--health-interval",
+  "--health-retries",
+  "--health-start-period",
+  "--health-timeout",
+  "--log-driver",
+  "--log-opt",
+  "--cap-add",
+  "--cap-drop",
+  "--device",
+  "--security-opt",
+  "--stop-signal",
+  "--stop-timeout",
+];
+
+// Flags that only matter for the `docker run` invocation itself
+const IGNORED_FLAGS: &[&str] = &[
+  "-d",
+  "--detach",
+  "-i",
+  "--interactive",
+  "-t",
+  "--tty",
+  "-it",
+  "-ti",
+];
+
+impl TryFrom<ContainerDefinition> for ImportedDeployment {
+  type Error = anyhow::Error;
+
+  fn try_from(source: ContainerDefinition) -> Result<Self, Self::Error> {
+    let image = source.image.context("image is missing")?;
+    if !is_atlas_local_image(&image) {
+      anyhow::bail!("{image} is not an atlas-local image");
+    }
+
+    let mut unsupported_keys = source.unsupported_keys;
+    let mut options = CreateDeploymentOptions {
+      name: source.name,
+      ..Default::default()
+    };
+
+    let (repository, digest) = match image.split_once('@') {
+      Some((repository, digest)) => (repository, Some(digest.to_string())),
+      None => (image.as_str(), None),
+    };
+    let (repository, tag) = match repository.rsplit_once(':') {
+      Some((name, tag)) if !tag.contains('/') => (name, Some(tag.to_string())),
+      _ => (repository, None),
+    };
+    if repository != ATLAS_LOCAL_IMAGE {
+      options.image = Some(repository.to_string());
+    }
+    options.image_tag = tag;
+    options.image_digest = digest;
+
+    for (key, value) in source.environment {
+      match key.as_str() {
+        "MONGODB_INITDB_DATABASE" => options.mongodb_initdb_database = Some(value),
+        "MONGODB_INITDB_ROOT_PASSWORD_FILE" => {
+          options.mongodb_initdb_root_password_file = Some(value)
+        }
+        "MONGODB_INITDB_ROOT_PASSWORD" => options.mongodb_initdb_root_password = Some(value),
+        "MONGODB_INITDB_ROOT_USERNAME_FILE" => {
+          options.mongodb_initdb_root_username_file = Some(value)
+        }
+        "MONGODB_INITDB_ROOT_USERNAME" => options.mongodb_initdb_root_username = Some(value),
+        "MONGODB_LOAD_SAMPLE_DATA" => options.load_sample_data = Some(is_true(&value)),
+        "VOYAGE_API_KEY" => options.voyage_api_key = Some(value),
+        "MONGOT_LOG_FILE" => options.mongot_log_file = Some(value),
+        "RUNNER_LOG_FILE" => options.runner_log_file = Some(value),
+        "DO_NOT_TRACK" => options.do_not_track = Some(is_true(&value)),
+        "TELEMETRY_BASE_URL" => options.telemetry_base_url = Some(value),
+        // atlas-local records the creation source in the environment
+        "TOOL" => {
+          options.creation_source = Some(CreationSource::from(
+            atlas_local::models::CreationSource::from(value.as_str()),
+          ))
+        }
+        _ => unsupported_keys.push(format!("environment.{key}")),
+      }
+    }
+
+    for port in source.ports {
+      match port_binding(&port) {
+        Some((MONGODB_PORT, binding)) if options.mongodb_port_binding.is_none() => {
+          options.mongodb_port_binding = Some(binding)
+        }
+        Some((MONGODB_PORT, binding)) => options
+          .additional_port_bindings
+          .get_or_insert_with(Vec::new)
+          .push(binding),
+        Some((MONGOT_PORT, binding)) if options.mongot_port_binding.is_none() => {
+          options.mongot_port_binding = Some(binding)
+        }
+        _ => unsupported_keys.push(format!("ports.{port}")),
+      }
+    }
+
+    for volume in source.volumes {
+      match volume.split_once(':') {
+        Some((source, destination))
+          if destination.trim_end_matches(":ro") == INIT_DIRECTORY
+            && options.local_seed_location.is_none() =>
+        {
+          options.local_seed_location = Some(source.to_string())
+        }
+        Some((source, DATA_DIRECTORY)) if is_volume_name(source) => {
+          options.data_volume = Some(source.to_string())
+        }
+        // The image declares these volumes, the container gets them either way
+        None if volume == DATA_DIRECTORY || volume == CONFIG_DIRECTORY => {}
+        _ => unsupported_keys.push(format!("volumes.{volume}")),
+      }
+    }
+
+    for (key, value) in source.labels {
+      let imported = match key.as_str() {
+        // Set by atlas-local on every container it creates
+        LOCAL_DEPLOYMENT_LABEL => true,
+        MONGOD_CONFIG_LABEL => {
+          options.mongod_config = serde_json::from_str(&value).ok();
+          options.mongod_config.is_some()
+        }
+        SET_PARAMETERS_LABEL => {
+          options.set_parameters = serde_json::from_str(&value).ok();
+          options.set_parameters.is_some()
+        }
+        OPLOG_SIZE_LABEL => {
+          options.oplog_size_mb = value.parse().ok();
+          options.oplog_size_mb.is_some()
+        }
+        // The root password is read from its source again, instead of the tmpfs it was kept in
+        ROOT_PASSWORD_SOURCE_LABEL => match serde_json::from_str(&value) {
+          Ok(source) => {
+            match source {
+              SecretSource::File(path) => options.root_password_from_file = Some(path),
+              SecretSource::Env(name) => options.root_password_from_env = Some(name),
+              SecretSource::Provider(name) => options.root_password_secret = Some(name),
+            }
+            if options.mongodb_initdb_root_password_file == Some(secret_file(ROOT_PASSWORD_SECRET))
+            {
+              options.mongodb_initdb_root_password_file = None;
+            }
+            true
+          }
+          Err(_) => false,
+        },
+        _ => false,
+      };
+      if !imported {
+        unsupported_keys.push(format!("labels.{key}"));
+      }
+    }
+
+    // Server parameters and the config file are passed to mongod as arguments, the labels record
+    // the configuration as given
+    match parse_set_parameter_args(&source.command) {
+      Some(parameters) => {
+        if options.mongod_config.is_none()
+          && options.set_parameters.is_none()
+          && !parameters.is_empty()
+        {
+          options.set_parameters = Some(parameters);
+        }
+      }
+      None => unsupported_keys.push("command".to_string()),
+    }
+
+    Ok(Self {
+      options,
+      unsupported_keys,
+    })
+  }
+}
+
+fn is_atlas_local_image(image: &str) -> bool {
+  let repository = image.split('@').next().unwrap_or(image);
+  let repository = match repository.rsplit_once(':') {
+    Some((name, tag)) if !tag.contains('/') => name,
+    _ => repository,
+  };
+
+  unpinned_repository(repository).ends_with("mongodb-atlas-local")
+}
+
+// A volume name, other sources are paths on the host
+fn is_volume_name(source: &str) -> bool {
+  !source.is_empty()
+    && !source.contains('/')
+    && !source.starts_with('.')
+    && !source.starts_with('~')
+}
+
+// `--setParameter name=value` pairs, values are parsed as JSON when they can be
+// The config file written for `mongodConfig` is skipped, the label restores it
+fn parse_set_parameter_args(command: &[String]) -> Option<HashMap<String, serde_json::Value>> {
+  let mut parameters = HashMap::new();
+  for pair in command.chunks(2) {
+    let [flag, parameter] = pair else {
+      return None;
+    };
+    if flag == "--config" && *parameter == mongod_config_file() {
+      continue;
+    }
+    if flag != "--setParameter" {
+      return None;
+    }
+    let (name, value) = parameter.split_once('=')?;
+    let value =
+      serde_json::from_str(value).unwrap_or_else(|_| serde_json::Value::String(value.to_string()));
+    parameters.insert(name.to_string(), value);
+  }
+
+  Some(parameters)
+}
+
+fn is_true(value: &str) -> bool {
+  matches!(value.to_lowercase().as_str(), "true" | "1" | "yes")
+}
+
+// `[ip:]host_port:container_port[/tcp]`, returns the container port with the binding
+// Only the MongoDB and mongot ports can be published
+fn port_binding(port: &str) -> Option<(u16, MongoDBPortBinding)> {
+  let port = port.strip_suffix("/tcp").unwrap_or(port);
+  let (host, container_port) = port.rsplit_once(':').unwrap_or(("", port));
+  let container_port: u16 = container_port.parse().ok()?;
+  if container_port != MONGODB_PORT && container_port != MONGOT_PORT {
+    return None;
+  }
+
+  let (ip, host_port) = match host.rsplit_once(':') {
+    Some((ip, host_port)) => (ip.trim_start_matches('[').trim_end_matches(']'), host_port),
+    None => ("", host),
+  };
+  let host_port = match host_port {
+    "" => None,
+    host_port => Some(host_port.parse().ok()?),
+  };
+
+  let binding = MongoDBPortBinding::from_host(ip, host_port);
+  if binding.binding_type == BindingType::Specific {
+    ip.parse::<std::net::IpAddr>().ok()?;
+  }
+
+  Some((container_port, binding))
+}
+
+fn scalar(value: &Value) -> Option<String> {
+  match value {
+    Value::String(value) => Some(value.clone()),
+    Value::Number(value) => Some(value.to_string()),
+    Value::Bool(value) => Some(value.to_string()),
+    _ => None,
+  }
+}
+
+// Entries in the long syntax (a mapping per entry) are reported as unsupported
+fn short_syntax(key: &str, value: &Value, unsupported_keys: &mut Vec<String>) -> Vec<String> {
+  let Some(entries) = value.as_sequence() else {
+    unsupported_keys.push(key.to_string());
+    return Vec::new();
+  };
+
+  let mut values = Vec::new();
+  for (index, entry) in entries.iter().enumerate() {
+    match scalar(entry) {
+      Some(entry) => values.push(entry),
+      None => unsupported_keys.push(format!("{key}[{index}]")),
+    }
+  }
+
+  values
+}
+
+// A string is split like a shell would
+fn command(value: &Value) -> Option<Vec<String>> {
+  match value {
+    Value::String(command) => shell_words::split(command).ok(),
+    Value::Sequence(_) => Some(scalars(value)),
+    _ => None,
+  }
+}
+
+fn scalars(value: &Value) -> Vec<String> {
+  value
+    .as_sequence()
+    .map(|values| values.iter().filter_map(scalar).collect())
+    .unwrap_or_default()
+}
+
+// Compose accepts both a mapping and a list of `KEY=value` for the environment and labels
+fn key_values(value: &Value) -> Vec<(String, String)> {
+  match value {
+    Value::Mapping(mapping) => mapping
+      .iter()
+      .filter_map(|(key, value)| Some((scalar(key)?, scalar(value).unwrap_or_default())))
+      .collect(),
+    Value::Sequence(_) => scalars(value)
+      .into_iter()
+      .filter_map(|variable| {
+        variable
+          .split_once('=')
+          .map(|(key, value)| (key.to_string(), value.to_string()))
+      })
+      .collect(),
+    _ => Vec::new(),
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use bollard::models::{
+    ContainerConfig, ContainerInspectResponse, HostConfig, ImageInspect, MountPoint, PortBinding,
+  };
+
+  use super::*;
+  use crate::models::compose::ComposeFile;
+  use crate::models::list_deployments::CreationSourceType;
+
+  #[test]
+  fn test_parse_compose_service() {
+    let imported = parse_compose_service(
+      r#"
+services:
+  app:
+    image: node:22
+  database:
+    image: mongodb/mongodb-atlas-local:8.0.4
+    container_name: test_deployment
+    restart: unless-stopped
+    environment:
+      MONGODB_INITDB_ROOT_USERNAME: admin
+      MONGODB_INITDB_ROOT_PASSWORD: password123
+      DO_NOT_TRACK: "true"
+      EXTRA: value
+    ports:
+      - "127.0.0.1:27018:27017"
+    volumes:
+      - ./seed:/docker-entrypoint-initdb.d
+      - data:/data/db
+volumes:
+  data:
+"#,
+      None,
+    )
+    .unwrap();
+
+    let options = imported.options;
+    assert_eq!(options.name, Some("test_deployment".to_string()));
+    assert_eq!(options.image, None);
+    assert_eq!(options.image_tag, Some("8.0.4".to_string()));
+    assert_eq!(
+      options.mongodb_initdb_root_username,
+      Some("admin".to_string())
+    );
+    assert_eq!(
+      options.mongodb_initdb_root_password,
+      Some("password123".to_string())
+    );
+    assert_eq!(options.do_not_track, Some(true));
+    assert_eq!(
+      options.mongodb_port_binding,
+      Some(MongoDBPortBinding {
+        binding_type: BindingType::Loopback,
+        ip: "127.0.0.1".to_string(),
+        port: Some(27018),
+      })
+    );
+    assert_eq!(options.local_seed_location, Some("./seed".to_string()));
+    assert_eq!(options.data_volume, Some("data".to_string()));
+    assert_eq!(
+      imported.unsupported_keys,
+      vec!["restart".to_string(), "environment.EXTRA".to_string()]
+    );
+  }
+
+  #[test]
+  fn test_parse_compose_service_by_name() {
+    let imported = parse_compose_service(
+      r#"
+services:
+  database:
+    image: mongodb/mongodb-atlas-local@sha256:digest
+    environment:
+      - MONGODB_INITDB_DATABASE=testdb
+"#,
+      Some("database"),
+    )
+    .unwrap();
+
+    assert_eq!(imported.options.name, Some("database".to_string()));
+    assert_eq!(imported.options.image_tag, None);
+    assert_eq!(
+      imported.options.image_digest,
+      Some("sha256:digest".to_string())
+    );
+    assert_eq!(
+      imported.options.mongodb_initdb_database,
+      Some("testdb".to_string())
+    );
+    assert!(imported.unsupported_keys.is_empty());
+  }
+
+  #[test]
+  fn test_parse_compose_service_without_atlas_local_image() {
+    let yaml = r#"
+services:
+  app:
+    image: node:22
+"#;
+
+    assert!(parse_compose_service(yaml, None).is_err());
+    assert!(parse_compose_service(yaml, Some("app")).is_err());
+    assert!(parse_compose_service(yaml, Some("database")).is_err());
+  }
+
+  #[test]
+  fn test_parse_docker_run() {
+    let imported = parse_docker_run(
+      "docker run -d --name test_deployment -p 27017:27017 -e DO_NOT_TRACK=1 \
+       --env 'MONGODB_INITDB_ROOT_USERNAME=admin' --memory=2g \
+       mongodb/mongodb-atlas-local:latest",
+    )
+    .unwrap();
+
+    let options = imported.options;
+    assert_eq!(options.name, Some("test_deployment".to_string()));
+    assert_eq!(options.image_tag, Some("latest".to_string()));
+    assert_eq!(options.do_not_track, Some(true));
+    assert_eq!(
+      options.mongodb_initdb_root_username,
+      Some("admin".to_string())
+    );
+    assert_eq!(
+      options.mongodb_port_binding,
+      Some(MongoDBPortBinding {
+        binding_type: BindingType::AnyInterface,
+        ip: "0.0.0.0".to_string(),
+        port: Some(27017),
+      })
+    );
+    assert_eq!(imported.unsupported_keys, vec!["--memory".to_string()]);
+  }
+
+  #[test]
+  fn test_parse_compose_service_long_syntax() {
+    let imported = parse_compose_service(
+      r#"
+services:
+  database:
+    image: mongodb/mongodb-atlas-local
+    ports:
+      - "27017:27017"
+      - target: 27027
+        published: "27027"
+    volumes:
+      - type: volume
+        source: data
+        target: /data/db
+"#,
+      None,
+    )
+    .unwrap();
+
+    assert!(imported.options.mongodb_port_binding.is_some());
+    assert_eq!(imported.options.mongot_port_binding, None);
+    assert_eq!(
+      imported.unsupported_keys,
+      vec!["ports[1]".to_string(), "volumes[0]".to_string()]
+    );
+  }
+
+  #[test]
+  fn test_parse_docker_run_unknown_flag() {
+    let error = parse_docker_run("docker run --unknown mongodb/mongodb-atlas-local").unwrap_err();
+
+    assert_eq!(error.to_string(), "unknown docker run flag --unknown");
+    assert!(parse_docker_run("docker run --unknown=value mongodb/mongodb-atlas-local").is_ok());
+  }
+
+  #[test]
+  fn test_parse_docker_run_with_command() {
+    let imported = parse_docker_run("docker run --rm mongodb/mongodb-atlas-local mongod").unwrap();
+
+    assert_eq!(imported.options.image_tag, None);
+    assert_eq!(
+      imported.unsupported_keys,
+      vec!["--rm".to_string(), "command".to_string()]
+    );
+  }
+
+  #[test]
+  fn test_parse_docker_run_with_labels_and_server_parameters() {
+    let imported = parse_docker_run(
+      "docker run -l mongodb-atlas-local=container --label team=search \
+       mongodb/mongodb-atlas-local-pinned-0123456789abcdef:8.0.4 \
+       --setParameter notablescan=true --setParameter diagnosticDataCollectionEnabled=false",
+    )
+    .unwrap();
+
+    assert_eq!(
+      imported.options.image,
+      Some("mongodb/mongodb-atlas-local-pinned-0123456789abcdef".to_string())
+    );
+    assert_eq!(
+      imported.options.set_parameters,
+      Some(HashMap::from([
+        ("notablescan".to_string(), serde_json::Value::Bool(true)),
+        (
+          "diagnosticDataCollectionEnabled".to_string(),
+          serde_json::Value::Bool(false)
+        ),
+      ]))
+    );
+    assert_eq!(imported.unsupported_keys, vec!["labels.team".to_string()]);
+  }
+
+  #[test]
+  fn test_parse_docker_run_with_mongod_config_file() {
+    let imported = parse_docker_run(
+      "docker run -l 'mongodb-atlas-local.mongod-config={\"operationProfiling\":{\"mode\":\"all\"}}' \
+       mongodb/mongodb-atlas-local --config /data/configdb/atlas-local-mongod.conf",
+    )
+    .unwrap();
+
+    assert_eq!(
+      imported.options.mongod_config,
+      Some(serde_json::json!({ "operationProfiling": { "mode": "all" } }))
+    );
+    assert!(imported.unsupported_keys.is_empty());
+
+    let imported =
+      parse_docker_run("docker run mongodb/mongodb-atlas-local --config /etc/mongod.conf").unwrap();
+    assert_eq!(imported.unsupported_keys, vec!["command".to_string()]);
+  }
+
+  #[test]
+  fn test_parse_docker_run_with_root_password_source() {
+    let imported = parse_docker_run(
+      "docker run -e MONGODB_INITDB_ROOT_USERNAME=admin \
+       -e MONGODB_INITDB_ROOT_PASSWORD_FILE=/run/atlas-local-secrets/root-password \
+       -l 'mongodb-atlas-local.root-password-source={\"env\":\"ROOT_PASSWORD\"}' \
+       mongodb/mongodb-atlas-local",
+    )
+    .unwrap();
+
+    assert_eq!(
+      imported.options.root_password_from_env,
+      Some("ROOT_PASSWORD".to_string())
+    );
+    assert_eq!(imported.options.mongodb_initdb_root_password_file, None);
+    assert!(imported.unsupported_keys.is_empty());
+  }
+
+  #[test]
+  fn test_export_compose_import_round_trip() {
+    let container = ContainerInspectResponse {
+      name: Some("/test_deployment".to_string()),
+      config: Some(ContainerConfig {
+        image: Some("mongodb/mongodb-atlas-local-pinned-0123456789abcdef:8.0.4".to_string()),
+        cmd: Some(vec![
+          "--setParameter".to_string(),
+          "notablescan=true".to_string(),
+        ]),
+        env: Some(vec![
+          "MONGODB_INITDB_ROOT_USERNAME=admin".to_string(),
+          "TOOL=MCPSERVER".to_string(),
+        ]),
+        labels: Some(HashMap::from([
+          (LOCAL_DEPLOYMENT_LABEL.to_string(), "container".to_string()),
+          (
+            SET_PARAMETERS_LABEL.to_string(),
+            r#"{"notablescan":true}"#.to_string(),
+          ),
+        ])),
+        ..Default::default()
+      }),
+      host_config: Some(HostConfig {
+        port_bindings: Some(HashMap::from([(
+          "27017/tcp".to_string(),
+          Some(vec![PortBinding {
+            host_ip: Some("127.0.0.1".to_string()),
+            host_port: Some("27018".to_string()),
+          }]),
+        )])),
+        ..Default::default()
+      }),
+      mounts: Some(vec![
+        MountPoint {
+          typ: Some("volume".to_string()),
+          name: Some("test_deployment_data".to_string()),
+          destination: Some(DATA_DIRECTORY.to_string()),
+          ..Default::default()
+        },
+        MountPoint {
+          typ: Some("volume".to_string()),
+          name: Some("a".repeat(64)),
+          destination: Some(CONFIG_DIRECTORY.to_string()),
+          ..Default::default()
+        },
+      ]),
+      ..Default::default()
+    };
+    let image = ImageInspect {
+      repo_digests: Some(vec![
+        "mongodb/mongodb-atlas-local@sha256:0123456789abcdef0123456789abcdef".to_string(),
+      ]),
+      ..Default::default()
+    };
+    let mut compose = ComposeFile::default();
+    compose.add_service(container, image);
+
+    let imported = parse_compose_service(&compose.to_yaml().unwrap(), None).unwrap();
+
+    let options = imported.options;
+    assert_eq!(options.name, Some("test_deployment".to_string()));
+    assert_eq!(options.image, None);
+    assert_eq!(
+      options.image_digest,
+      Some("sha256:0123456789abcdef0123456789abcdef".to_string())
+    );
+    assert_eq!(
+      options.mongodb_initdb_root_username,
+      Some("admin".to_string())
+    );
+    assert_eq!(
+      options.creation_source,
+      Some(CreationSource {
+        source_type: CreationSourceType::MCPServer,
+        source: "MCPSERVER".to_string(),
+      })
+    );
+    assert_eq!(
+      options.mongodb_port_binding,
+      Some(MongoDBPortBinding {
+        binding_type: BindingType::Loopback,
+        ip: "127.0.0.1".to_string(),
+        port: Some(27018),
+      })
+    );
+    assert_eq!(
+      options.data_volume,
+      Some("test_deployment_data".to_string())
+    );
+    assert_eq!(
+      options.set_parameters,
+      Some(HashMap::from([(
+        "notablescan".to_string(),
+        serde_json::Value::Bool(true)
+      )]))
+    );
+    assert!(imported.unsupported_keys.is_empty());
+  }
+
+  #[test]
+  fn test_port_binding() {
+    assert_eq!(
+      port_binding("27017"),
+      Some((
+        27017,
+        MongoDBPortBinding {
+          binding_type: BindingType::AnyInterface,
+          ip: "0.0.0.0".to_string(),
+          port: None,
+        }
+      ))
+    );
+    assert_eq!(
+      port_binding("192.0.2.0:27018:27017/tcp"),
+      Some((
+        27017,
+        MongoDBPortBinding {
+          binding_type: BindingType::Specific,
+          ip: "192.0.2.0".to_string(),
+          port: Some(27018),
+        }
+      ))
+    );
+    assert_eq!(
+      port_binding("[::1]:27027:27027"),
+      Some((
+        27027,
+        MongoDBPortBinding {
+          binding_type: BindingType::LoopbackIpv6,
+          ip: "::1".to_string(),
+          port: Some(27027),
+        }
+      ))
+    );
+    assert_eq!(port_binding("not-an-ip:27018:27017"), None);
+    assert_eq!(port_binding("8080:80"), None);
+  }
+}
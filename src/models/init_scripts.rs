@@ -0,0 +1,181 @@
+use std::path::Path;
+
+use anyhow::{Context, Result};
+use napi_derive::napi;
+
+// Either a script file on the host (`path`) or an inline script (`name`, `content` and `type`)
+#[napi(object)]
+#[derive(Debug)]
+pub struct InitScript {
+  pub path: Option<String>,
+  pub name: Option<String>,
+  pub content: Option<String>,
+  #[napi(js_name = "type")]
+  pub script_type: Option<InitScriptType>,
+}
+
+#[napi(string_enum = "lowercase")]
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub enum InitScriptType {
+  Js,
+  Sh,
+}
+
+#[napi(object)]
+#[derive(Clone, PartialEq, Debug)]
+pub struct InitScriptOutput {
+  pub name: String,
+  pub exit_code: i64,
+  pub stdout: String,
+  pub stderr: String,
+}
+
+#[derive(PartialEq, Debug)]
+pub(crate) struct ResolvedInitScript {
+  pub name: String,
+  pub content: String,
+  pub script_type: InitScriptType,
+}
+
+// Scripts are copied here instead of the init directory of the entrypoint, which can be a bind
+// mount of `local_seed_location` on the host
+pub(crate) const INIT_SCRIPTS_DIRECTORY: &str = "/tmp/atlas-local-init-scripts";
+
+impl ResolvedInitScript {
+  // Scripts run one at a time, the name of the script is not used so it needs no escaping
+  pub(crate) fn container_path(&self) -> String {
+    let extension = match self.script_type {
+      InitScriptType::Js => "js",
+      InitScriptType::Sh => "sh",
+    };
+
+    format!("{INIT_SCRIPTS_DIRECTORY}/init-script.{extension}")
+  }
+}
+
+// Like the entrypoint of the image, the type of a script file follows from its extension
+fn script_type_from_name(name: &str) -> Option<InitScriptType> {
+  match Path::new(name).extension()?.to_str()? {
+    "js" => Some(InitScriptType::Js),
+    "sh" => Some(InitScriptType::Sh),
+    _ => None,
+  }
+}
+
+pub(crate) fn resolve_init_script(script: InitScript) -> Result<ResolvedInitScript> {
+  match script {
+    InitScript {
+      path: Some(path),
+      content: None,
+      script_type,
+      ..
+    } => {
+      let name = Path::new(&path)
+        .file_name()
+        .and_then(|name| name.to_str())
+        .unwrap_or(&path)
+        .to_string();
+      let script_type = script_type
+        .or_else(|| script_type_from_name(&name))
+        .with_context(|| format!("init script {path} must be a .js or .sh file"))?;
+      let content =
+        std::fs::read_to_string(&path).with_context(|| format!("read init script {path}"))?;
+
+      Ok(ResolvedInitScript {
+        name,
+        content,
+        script_type,
+      })
+    }
+    InitScript {
+      path: None,
+      name: Some(name),
+      content: Some(content),
+      script_type,
+    } => {
+      let script_type = script_type
+        .or_else(|| script_type_from_name(&name))
+        .with_context(|| format!("init script {name} needs a type"))?;
+
+      Ok(ResolvedInitScript {
+        name,
+        content,
+        script_type,
+      })
+    }
+    _ => anyhow::bail!("init scripts need either a path or a name and content"),
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  fn inline(name: &str, script_type: Option<InitScriptType>) -> InitScript {
+    InitScript {
+      path: None,
+      name: Some(name.to_string()),
+      content: Some("db.users.insertOne({ name: 'test' })".to_string()),
+      script_type,
+    }
+  }
+
+  #[test]
+  fn test_resolve_inline_init_script() {
+    assert_eq!(
+      resolve_init_script(inline("users", Some(InitScriptType::Js))).unwrap(),
+      ResolvedInitScript {
+        name: "users".to_string(),
+        content: "db.users.insertOne({ name: 'test' })".to_string(),
+        script_type: InitScriptType::Js,
+      }
+    );
+    assert_eq!(
+      resolve_init_script(inline("setup.sh", None))
+        .unwrap()
+        .script_type,
+      InitScriptType::Sh
+    );
+    assert!(resolve_init_script(inline("users", None)).is_err());
+  }
+
+  #[test]
+  fn test_container_path() {
+    let script = resolve_init_script(inline("../users; rm -rf", Some(InitScriptType::Js))).unwrap();
+
+    assert_eq!(
+      script.container_path(),
+      "/tmp/atlas-local-init-scripts/init-script.js"
+    );
+  }
+
+  #[test]
+  fn test_resolve_init_script_file() {
+    let path = std::env::temp_dir().join("atlas-local-init-script-test.js");
+    std::fs::write(&path, "db.users.drop()").unwrap();
+
+    let script = resolve_init_script(InitScript {
+      path: Some(path.to_string_lossy().to_string()),
+      name: None,
+      content: None,
+      script_type: None,
+    })
+    .unwrap();
+    std::fs::remove_file(&path).unwrap();
+
+    assert_eq!(script.name, "atlas-local-init-script-test.js");
+    assert_eq!(script.content, "db.users.drop()");
+    assert_eq!(script.script_type, InitScriptType::Js);
+  }
+
+  #[test]
+  fn test_resolve_init_script_needs_path_or_content() {
+    assert!(resolve_init_script(InitScript {
+      path: None,
+      name: Some("users.js".to_string()),
+      content: None,
+      script_type: None,
+    })
+    .is_err());
+  }
+}
@@ -1,6 +1,12 @@
-use std::net::IpAddr;
+use std::collections::HashMap;
+use std::net::{IpAddr, Ipv6Addr};
 
 use napi_derive::napi;
+use serde_json::Value;
+
+use crate::models::auth::GeneratedCredentials;
+use crate::models::init_scripts::InitScriptOutput;
+use crate::models::networks::DeploymentNetwork;
 
 #[napi(object)]
 pub struct Deployment {
@@ -10,24 +16,46 @@ pub struct Deployment {
 
   // Docker specific
   pub state: State,
-  pub port_bindings: Option<MongoDBPortBinding>,
+  pub port_bindings: Vec<MongoDBPortBinding>,
+  pub mongot_port_bindings: Vec<MongoDBPortBinding>,
+  pub networks: Vec<DeploymentNetwork>,
+  pub internal_ip: Option<String>,
+  // Pinned images report the repository they were pinned from
+  pub image: Option<String>,
+  pub image_tag: Option<String>,
+  pub image_digest: Option<String>,
+  pub labels: HashMap<String, String>,
 
   // MongoDB details (MongoD)
   pub mongodb_type: MongodbType,
   pub mongodb_version: String,
+  // Read from the running mongod, only reported by `getDeployment`
+  pub replica_set: Option<String>,
+  pub change_streams_available: Option<bool>,
+  pub transactions_available: Option<bool>,
+  #[napi(js_name = "oplogSizeMB")]
+  pub oplog_size_mb: Option<u32>,
+  pub mongod_config: Option<Value>,
+  pub set_parameters: Option<HashMap<String, Value>>,
 
   // Creation source
   pub creation_source: Option<CreationSource>,
 
   // Initial database configuration
   pub local_seed_location: Option<String>,
+  pub data_volume: Option<String>,
   pub mongodb_initdb_database: Option<String>,
   pub mongodb_initdb_root_password_file: Option<String>,
   pub mongodb_initdb_root_password: Option<String>,
   pub mongodb_initdb_root_username_file: Option<String>,
   pub mongodb_initdb_root_username: Option<String>,
   pub mongodb_load_sample_data: Option<bool>,
+  // Datasets loaded with `sampleData` are only reported by `getDeployment`
+  pub sample_datasets: Vec<String>,
   pub voyage_api_key: Option<String>,
+  // Only reported by `createDeployment`
+  pub init_script_outputs: Option<Vec<InitScriptOutput>>,
+  pub generated_credentials: Option<GeneratedCredentials>,
 
   // Logging
   pub mongot_log_file: Option<String>,
@@ -36,6 +64,11 @@ pub struct Deployment {
   // Telemetry
   pub do_not_track: bool,
   pub telemetry_base_url: Option<String>,
+
+  // Cleanup
+  pub expires_at: Option<i64>,
+  pub owner_pid: Option<u32>,
+  pub owner_hostname: Option<String>,
 }
 
 #[napi(string_enum)]
@@ -63,10 +96,43 @@ pub struct MongoDBPortBinding {
 #[derive(PartialEq, Debug)]
 pub enum BindingType {
   Loopback,     // 127.0.0.1
+  LoopbackIpv6, // ::1
   AnyInterface, // 0.0.0.0
   Specific,     // Specific IP address
 }
 
+impl MongoDBPortBinding {
+  // Binding of a port published on `host_ip` of the Docker host, an empty IP means any interface
+  pub(crate) fn from_host(host_ip: &str, port: Option<u16>) -> Self {
+    let binding_type = match host_ip {
+      "127.0.0.1" => BindingType::Loopback,
+      "::1" => BindingType::LoopbackIpv6,
+      "" | "0.0.0.0" | "::" => BindingType::AnyInterface,
+      _ => BindingType::Specific,
+    };
+    let ip = match binding_type {
+      BindingType::AnyInterface => "0.0.0.0",
+      _ => host_ip,
+    };
+
+    MongoDBPortBinding {
+      binding_type,
+      ip: ip.to_string(),
+      port,
+    }
+  }
+
+  // Address the port is published on, as Docker expects it
+  pub(crate) fn host_ip(&self) -> String {
+    match self.binding_type {
+      BindingType::Loopback => "127.0.0.1".to_string(),
+      BindingType::LoopbackIpv6 => "::1".to_string(),
+      BindingType::AnyInterface => "0.0.0.0".to_string(),
+      BindingType::Specific => self.ip.clone(),
+    }
+  }
+}
+
 #[napi(string_enum)]
 #[derive(PartialEq, Debug)]
 pub enum MongodbType {
@@ -98,22 +164,46 @@ impl From<atlas_local::models::Deployment> for Deployment {
       container_id: source.container_id,
       name: source.name,
       state: source.state.into(),
-      port_bindings: source.port_bindings.map(MongoDBPortBinding::from),
+      port_bindings: source
+        .port_bindings
+        .map(MongoDBPortBinding::from)
+        .into_iter()
+        .collect(),
+      mongot_port_bindings: Vec::new(),
+      networks: Vec::new(),
+      internal_ip: None,
+      image: None,
+      image_tag: None,
+      image_digest: None,
+      labels: HashMap::new(),
       mongodb_type: source.mongodb_type.into(),
       mongodb_version: source.mongodb_version.to_string(),
+      replica_set: None,
+      change_streams_available: None,
+      transactions_available: None,
+      oplog_size_mb: None,
+      mongod_config: None,
+      set_parameters: None,
       creation_source: source.creation_source.map(CreationSource::from),
       local_seed_location: source.local_seed_location,
+      data_volume: None,
       mongodb_initdb_database: source.mongodb_initdb_database,
       mongodb_initdb_root_password_file: source.mongodb_initdb_root_password_file,
       mongodb_initdb_root_password: source.mongodb_initdb_root_password,
       mongodb_initdb_root_username_file: source.mongodb_initdb_root_username_file,
       mongodb_initdb_root_username: source.mongodb_initdb_root_username,
       mongodb_load_sample_data: source.mongodb_load_sample_data,
+      sample_datasets: Vec::new(),
       voyage_api_key: source.voyage_api_key,
+      init_script_outputs: None,
+      generated_credentials: None,
       mongot_log_file: source.mongot_log_file,
       runner_log_file: source.runner_log_file,
       do_not_track: source.do_not_track,
       telemetry_base_url: source.telemetry_base_url,
+      expires_at: None,
+      owner_pid: None,
+      owner_hostname: None,
     }
   }
 }
@@ -163,6 +253,12 @@ impl From<MongoDBPortBinding> for atlas_local::models::MongoDBPortBinding {
         binding_type: atlas_local::models::BindingType::Loopback,
         port: source.port,
       },
+      BindingType::LoopbackIpv6 => atlas_local::models::MongoDBPortBinding {
+        binding_type: atlas_local::models::BindingType::Specific {
+          ip: IpAddr::V6(Ipv6Addr::LOCALHOST),
+        },
+        port: source.port,
+      },
       BindingType::AnyInterface => atlas_local::models::MongoDBPortBinding {
         binding_type: atlas_local::models::BindingType::AnyInterface,
         port: source.port,
@@ -227,6 +323,52 @@ impl From<CreationSource> for atlas_local::models::CreationSource {
   }
 }
 
+// Running deployment without any optional details, shared by the tests of the models
+#[cfg(test)]
+pub(crate) fn test_deployment(name: &str) -> Deployment {
+  Deployment {
+    container_id: format!("{name}_container_id"),
+    name: Some(name.to_string()),
+    state: State::Running,
+    port_bindings: vec![],
+    mongot_port_bindings: vec![],
+    networks: vec![],
+    internal_ip: None,
+    image: None,
+    image_tag: None,
+    image_digest: None,
+    labels: HashMap::new(),
+    mongodb_type: MongodbType::Community,
+    mongodb_version: "8.0.4".to_string(),
+    replica_set: None,
+    change_streams_available: None,
+    transactions_available: None,
+    oplog_size_mb: None,
+    mongod_config: None,
+    set_parameters: None,
+    creation_source: None,
+    local_seed_location: None,
+    data_volume: None,
+    mongodb_initdb_database: None,
+    mongodb_initdb_root_password_file: None,
+    mongodb_initdb_root_password: None,
+    mongodb_initdb_root_username_file: None,
+    mongodb_initdb_root_username: None,
+    mongodb_load_sample_data: None,
+    sample_datasets: vec![],
+    voyage_api_key: None,
+    init_script_outputs: None,
+    generated_credentials: None,
+    mongot_log_file: None,
+    runner_log_file: None,
+    do_not_track: false,
+    telemetry_base_url: None,
+    expires_at: None,
+    owner_pid: None,
+    owner_hostname: None,
+  }
+}
+
 #[cfg(test)]
 mod tests {
   use semver::Version;
@@ -265,13 +407,24 @@ mod tests {
     assert_eq!(deployment.container_id, "container_id");
     assert_eq!(deployment.name, Some("test_deployment".to_string()));
     assert_eq!(deployment.state, State::Running);
-    assert!(deployment.port_bindings.is_some());
-    let port_binding = deployment.port_bindings.unwrap();
-    assert_eq!(port_binding.binding_type, BindingType::Loopback);
-    assert_eq!(port_binding.ip, "127.0.0.1");
-    assert_eq!(port_binding.port, Some(27017));
+    assert_eq!(
+      deployment.port_bindings,
+      vec![MongoDBPortBinding {
+        binding_type: BindingType::Loopback,
+        ip: "127.0.0.1".to_string(),
+        port: Some(27017),
+      }]
+    );
+    assert!(deployment.mongot_port_bindings.is_empty());
+    assert!(deployment.networks.is_empty());
+    assert_eq!(deployment.internal_ip, None);
+    assert_eq!(deployment.image_tag, None);
+    assert_eq!(deployment.image_digest, None);
     assert_eq!(deployment.mongodb_type, MongodbType::Community);
     assert_eq!(deployment.mongodb_version, "8.0.0");
+    assert_eq!(deployment.replica_set, None);
+    assert_eq!(deployment.mongod_config, None);
+    assert_eq!(deployment.set_parameters, None);
     assert_eq!(
       deployment.creation_source,
       Some(CreationSource {
@@ -304,10 +457,13 @@ mod tests {
       Some("admin".to_string())
     );
     assert_eq!(deployment.mongodb_load_sample_data, Some(false));
+    assert!(deployment.sample_datasets.is_empty());
     assert_eq!(
       deployment.voyage_api_key,
       Some("voyage_api_key".to_string())
     );
+    assert_eq!(deployment.init_script_outputs, None);
+    assert_eq!(deployment.generated_credentials, None);
     assert_eq!(
       deployment.mongot_log_file,
       Some("/tmp/mongot.log".to_string())
@@ -316,11 +472,14 @@ mod tests {
       deployment.runner_log_file,
       Some("/tmp/runner.log".to_string())
     );
-    assert_eq!(deployment.do_not_track, false);
+    assert!(!deployment.do_not_track);
     assert_eq!(
       deployment.telemetry_base_url,
       Some("https://telemetry.example.com".to_string())
     );
+    assert_eq!(deployment.expires_at, None);
+    assert_eq!(deployment.owner_pid, None);
+    assert_eq!(deployment.owner_hostname, None);
   }
 
   #[test]
@@ -410,6 +569,48 @@ mod tests {
     assert_eq!(lib_mongodb_port_binding.port, Some(27017));
   }
 
+  #[test]
+  fn test_mongodb_port_binding_lib_into_mongodb_port_binding_loopback_ipv6() {
+    let mongodb_port_binding = MongoDBPortBinding {
+      binding_type: BindingType::LoopbackIpv6,
+      ip: "::1".to_string(),
+      port: Some(27017),
+    };
+    let lib_mongodb_port_binding: atlas_local::models::MongoDBPortBinding =
+      mongodb_port_binding.into();
+    assert_eq!(
+      lib_mongodb_port_binding.binding_type,
+      atlas_local::models::BindingType::Specific {
+        ip: IpAddr::V6(Ipv6Addr::LOCALHOST)
+      }
+    );
+    assert_eq!(lib_mongodb_port_binding.port, Some(27017));
+  }
+
+  #[test]
+  fn test_mongodb_port_binding_from_host() {
+    assert_eq!(
+      MongoDBPortBinding::from_host("::1", Some(27017)),
+      MongoDBPortBinding {
+        binding_type: BindingType::LoopbackIpv6,
+        ip: "::1".to_string(),
+        port: Some(27017),
+      }
+    );
+    assert_eq!(
+      MongoDBPortBinding::from_host("", None),
+      MongoDBPortBinding {
+        binding_type: BindingType::AnyInterface,
+        ip: "0.0.0.0".to_string(),
+        port: None,
+      }
+    );
+    assert_eq!(
+      MongoDBPortBinding::from_host("192.0.2.0", Some(27018)).binding_type,
+      BindingType::Specific
+    );
+  }
+
   #[test]
   fn test_creation_source_from_lib_creation_source_atlas_cli() {
     let lib_creation_source = atlas_local::models::CreationSource::AtlasCLI;
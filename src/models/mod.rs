@@ -1,2 +1,23 @@
+pub mod auth;
+pub mod compose;
+pub mod connection_string;
 pub mod create_deployment;
+pub mod deployment_filter;
+pub mod deployment_spec;
+pub mod deployment_stats;
+pub mod expiration;
+pub mod image_tag;
+pub mod images;
+pub mod import_deployment;
+pub mod init_scripts;
 pub mod list_deployments;
+pub mod networks;
+pub mod ports;
+pub mod replica_set;
+pub mod sample_data;
+pub mod secrets;
+pub mod seed;
+pub mod server_parameters;
+pub mod server_status;
+pub mod update_deployment;
+pub mod users;
@@ -0,0 +1,136 @@
+use std::collections::HashMap;
+
+use bollard::models::EndpointSettings;
+use napi_derive::napi;
+
+// Network Docker connects containers to when no network is given
+pub(crate) const DEFAULT_NETWORK: &str = "bridge";
+
+#[napi(object)]
+#[derive(Debug)]
+pub struct NetworkOptions {
+  pub name: String,
+  pub aliases: Option<Vec<String>>,
+  // Creates a bridge network with this name when it doesn't exist yet
+  pub create: Option<bool>,
+}
+
+#[napi(object)]
+#[derive(Clone, PartialEq, Debug)]
+pub struct DeploymentNetwork {
+  pub name: String,
+  pub aliases: Vec<String>,
+  pub ip_address: Option<String>,
+}
+
+// `network_mode` is the network the container was created on, "default" being the bridge network
+pub(crate) fn primary_network(network_mode: Option<&str>) -> &str {
+  match network_mode {
+    None | Some("") | Some("default") => DEFAULT_NETWORK,
+    Some(network_mode) => network_mode,
+  }
+}
+
+// Networks of a container sorted by name
+// Docker adds the short container id as an alias, it is left out
+pub(crate) fn deployment_networks(
+  networks: &HashMap<String, EndpointSettings>,
+  container_id: &str,
+) -> Vec<DeploymentNetwork> {
+  let short_id = container_id.get(..12).unwrap_or(container_id);
+  let mut networks: Vec<DeploymentNetwork> = networks
+    .iter()
+    .map(|(name, endpoint)| DeploymentNetwork {
+      name: name.clone(),
+      aliases: endpoint
+        .aliases
+        .iter()
+        .flatten()
+        .filter(|alias| alias.as_str() != short_id)
+        .cloned()
+        .collect(),
+      ip_address: endpoint
+        .ip_address
+        .clone()
+        .filter(|ip_address| !ip_address.is_empty()),
+    })
+    .collect();
+  networks.sort_by(|a, b| a.name.cmp(&b.name));
+
+  networks
+}
+
+// Networks a recreated container has to be connected to besides the one it is created on
+pub(crate) fn networks_to_reconnect(
+  networks: &HashMap<String, EndpointSettings>,
+  network_mode: Option<&str>,
+  container_id: &str,
+) -> Vec<DeploymentNetwork> {
+  let primary = primary_network(network_mode);
+
+  deployment_networks(networks, container_id)
+    .into_iter()
+    .filter(|network| network.name != primary)
+    .collect()
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  const CONTAINER_ID: &str = "0123456789abcdef";
+
+  fn networks() -> HashMap<String, EndpointSettings> {
+    HashMap::from([
+      (
+        "bridge".to_string(),
+        EndpointSettings {
+          ip_address: Some("172.17.0.2".to_string()),
+          ..Default::default()
+        },
+      ),
+      (
+        "app".to_string(),
+        EndpointSettings {
+          aliases: Some(vec!["mongodb".to_string(), "0123456789ab".to_string()]),
+          ip_address: Some("172.18.0.3".to_string()),
+          ..Default::default()
+        },
+      ),
+    ])
+  }
+
+  #[test]
+  fn test_primary_network() {
+    assert_eq!(primary_network(None), "bridge");
+    assert_eq!(primary_network(Some("default")), "bridge");
+    assert_eq!(primary_network(Some("app")), "app");
+  }
+
+  #[test]
+  fn test_deployment_networks() {
+    assert_eq!(
+      deployment_networks(&networks(), CONTAINER_ID),
+      vec![
+        DeploymentNetwork {
+          name: "app".to_string(),
+          aliases: vec!["mongodb".to_string()],
+          ip_address: Some("172.18.0.3".to_string()),
+        },
+        DeploymentNetwork {
+          name: "bridge".to_string(),
+          aliases: vec![],
+          ip_address: Some("172.17.0.2".to_string()),
+        },
+      ]
+    );
+  }
+
+  #[test]
+  fn test_networks_to_reconnect() {
+    let networks = networks_to_reconnect(&networks(), Some("default"), CONTAINER_ID);
+
+    assert_eq!(networks.len(), 1);
+    assert_eq!(networks[0].name, "app");
+  }
+}
@@ -0,0 +1,399 @@
+use std::collections::HashMap;
+use std::io::ErrorKind;
+use std::net::{IpAddr, Ipv4Addr, Ipv6Addr, SocketAddr, TcpListener};
+
+use anyhow::{Context, Result};
+use bollard::models::{ContainerCreateBody, PortBinding};
+use napi_derive::napi;
+
+use crate::models::list_deployments::{BindingType, MongoDBPortBinding};
+
+#[napi(object)]
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub struct PortRange {
+  pub from: u16,
+  pub to: u16,
+}
+
+// Address the port is bound to on the host
+pub(crate) fn host_ip(binding: &MongoDBPortBinding) -> Result<IpAddr> {
+  match binding.binding_type {
+    BindingType::Loopback => Ok(IpAddr::V4(Ipv4Addr::LOCALHOST)),
+    BindingType::LoopbackIpv6 => Ok(IpAddr::V6(Ipv6Addr::LOCALHOST)),
+    BindingType::AnyInterface => Ok(IpAddr::V4(Ipv4Addr::UNSPECIFIED)),
+    BindingType::Specific => binding
+      .ip
+      .parse()
+      .with_context(|| format!("parse IP address {}", binding.ip)),
+  }
+}
+
+// Binding fails when another process (or a container publishing the port) listens on it
+// Other failures, like an address that isn't local to this host, are left for Docker to report
+pub(crate) fn is_port_free(ip: IpAddr, port: u16) -> bool {
+  !matches!(
+    TcpListener::bind(SocketAddr::new(ip, port)),
+    Err(error) if error.kind() == ErrorKind::AddrInUse
+  )
+}
+
+#[derive(PartialEq, Debug)]
+pub(crate) struct PortProcess {
+  pub pid: u32,
+  pub name: String,
+}
+
+// Process listening on `port`, best effort: only the processes this user can inspect are found
+// On Linux the listening socket is looked up in /proc, elsewhere lsof is asked
+#[cfg(target_os = "linux")]
+pub(crate) fn process_using_port(port: u16) -> Option<PortProcess> {
+  let mut inodes = Vec::new();
+  for table in ["/proc/net/tcp", "/proc/net/tcp6"] {
+    if let Ok(content) = std::fs::read_to_string(table) {
+      inodes.extend(listening_socket_inodes(&content, port));
+    }
+  }
+  if inodes.is_empty() {
+    return None;
+  }
+
+  for entry in std::fs::read_dir("/proc").ok()?.flatten() {
+    let Some(pid) = entry.file_name().to_str().and_then(|pid| pid.parse().ok()) else {
+      continue;
+    };
+    let Ok(fds) = std::fs::read_dir(entry.path().join("fd")) else {
+      continue;
+    };
+    let listens = fds.flatten().any(|fd| {
+      std::fs::read_link(fd.path())
+        .ok()
+        .and_then(|link| socket_inode(&link.to_string_lossy()))
+        .is_some_and(|inode| inodes.contains(&inode))
+    });
+    if listens {
+      let name = std::fs::read_to_string(entry.path().join("comm")).ok()?;
+      return Some(PortProcess {
+        pid,
+        name: name.trim_end().to_string(),
+      });
+    }
+  }
+
+  None
+}
+
+#[cfg(not(target_os = "linux"))]
+pub(crate) fn process_using_port(port: u16) -> Option<PortProcess> {
+  let output = std::process::Command::new("lsof")
+    .args(["-nP", &format!("-iTCP:{port}"), "-sTCP:LISTEN", "-Fpc"])
+    .output()
+    .ok()?;
+  parse_lsof_output(&String::from_utf8_lossy(&output.stdout))
+}
+
+// Inodes of the sockets listening on `port` in a /proc/net/tcp table
+// Lines look like `0: 0100007F:6989 00000000:0000 0A ... 1000 0 12345 ...`, with the local
+// address, the state (0A is LISTEN) and the inode in the 2nd, 4th and 10th columns
+#[cfg_attr(not(target_os = "linux"), allow(dead_code))]
+fn listening_socket_inodes(table: &str, port: u16) -> Vec<u64> {
+  table
+    .lines()
+    .skip(1)
+    .filter_map(|line| {
+      let columns: Vec<&str> = line.split_whitespace().collect();
+      let local_port = columns.get(1)?.rsplit_once(':')?.1;
+      let listening = u16::from_str_radix(local_port, 16).ok()? == port && *columns.get(3)? == "0A";
+      listening.then(|| columns.get(9)?.parse().ok()).flatten()
+    })
+    .collect()
+}
+
+// Inode of a file descriptor link, they look like `socket:[12345]` for sockets
+#[cfg_attr(not(target_os = "linux"), allow(dead_code))]
+fn socket_inode(link: &str) -> Option<u64> {
+  link
+    .strip_prefix("socket:[")?
+    .strip_suffix(']')?
+    .parse()
+    .ok()
+}
+
+// `lsof -F pc` prints a `p<pid>` line followed by a `c<command>` line per process
+#[cfg_attr(target_os = "linux", allow(dead_code))]
+fn parse_lsof_output(output: &str) -> Option<PortProcess> {
+  let mut lines = output.lines();
+  let pid = lines.next()?.strip_prefix('p')?.parse().ok()?;
+  let name = lines.next()?.strip_prefix('c')?.to_string();
+  Some(PortProcess { pid, name })
+}
+
+pub(crate) fn first_free_port(range: PortRange, is_free: impl Fn(u16) -> bool) -> Result<u16> {
+  if range.from > range.to {
+    anyhow::bail!("invalid port range {}-{}", range.from, range.to);
+  }
+
+  (range.from..=range.to)
+    .find(|port| is_free(*port))
+    .with_context(|| format!("no free port in range {}-{}", range.from, range.to))
+}
+
+// Bindings of `container_port` in the port map of a container
+pub(crate) fn port_bindings_from_docker(
+  ports: &HashMap<String, Option<Vec<PortBinding>>>,
+  container_port: u16,
+) -> Vec<MongoDBPortBinding> {
+  let mut bindings = Vec::new();
+  for binding in ports
+    .get(&format!("{container_port}/tcp"))
+    .cloned()
+    .flatten()
+    .unwrap_or_default()
+  {
+    let port = binding
+      .host_port
+      .as_deref()
+      .and_then(|port| port.parse().ok());
+    let binding =
+      MongoDBPortBinding::from_host(binding.host_ip.as_deref().unwrap_or_default(), port);
+    // Docker reports a binding on any interface for both IPv4 and IPv6
+    if !bindings.contains(&binding) {
+      bindings.push(binding);
+    }
+  }
+
+  bindings
+}
+
+// Publishes `container_port` on the given bindings as well, a binding without a port uses
+// `default_port`
+pub(crate) fn add_port_bindings(
+  body: &mut ContainerCreateBody,
+  container_port: u16,
+  bindings: &[MongoDBPortBinding],
+  default_port: Option<u16>,
+) {
+  if bindings.is_empty() {
+    return;
+  }
+
+  let key = format!("{container_port}/tcp");
+  let exposed_ports = body.exposed_ports.get_or_insert_with(Vec::new);
+  if !exposed_ports.contains(&key) {
+    exposed_ports.push(key.clone());
+  }
+  let published = body
+    .host_config
+    .get_or_insert_with(Default::default)
+    .port_bindings
+    .get_or_insert_with(HashMap::new)
+    .entry(key)
+    .or_default()
+    .get_or_insert_with(Vec::new);
+  for binding in bindings {
+    published.push(PortBinding {
+      host_ip: Some(binding.host_ip()),
+      host_port: binding.port.or(default_port).map(|port| port.to_string()),
+    });
+  }
+}
+
+// Replaces the bindings of `container_port` that let Docker pick a port with `port`, so the port
+// is kept when the container is recreated
+pub(crate) fn pin_port(body: &mut ContainerCreateBody, container_port: u16, port: u16) {
+  let published = body
+    .host_config
+    .as_mut()
+    .and_then(|host_config| host_config.port_bindings.as_mut())
+    .and_then(|ports| ports.get_mut(&format!("{container_port}/tcp")))
+    .and_then(|bindings| bindings.as_mut());
+  for binding in published.into_iter().flatten() {
+    if binding.host_port.as_deref().unwrap_or_default().is_empty() {
+      binding.host_port = Some(port.to_string());
+    }
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  fn binding(binding_type: BindingType, ip: &str) -> MongoDBPortBinding {
+    MongoDBPortBinding {
+      binding_type,
+      ip: ip.to_string(),
+      port: None,
+    }
+  }
+
+  #[test]
+  fn test_host_ip() {
+    assert_eq!(
+      host_ip(&binding(BindingType::Loopback, "")).unwrap(),
+      IpAddr::V4(Ipv4Addr::LOCALHOST)
+    );
+    assert_eq!(
+      host_ip(&binding(BindingType::LoopbackIpv6, "")).unwrap(),
+      IpAddr::V6(Ipv6Addr::LOCALHOST)
+    );
+    assert_eq!(
+      host_ip(&binding(BindingType::AnyInterface, "")).unwrap(),
+      IpAddr::V4(Ipv4Addr::UNSPECIFIED)
+    );
+    assert_eq!(
+      host_ip(&binding(BindingType::Specific, "192.168.1.10")).unwrap(),
+      "192.168.1.10".parse::<IpAddr>().unwrap()
+    );
+    assert!(host_ip(&binding(BindingType::Specific, "not an ip")).is_err());
+  }
+
+  #[test]
+  fn test_port_bindings_from_docker() {
+    let ports = HashMap::from([(
+      "27017/tcp".to_string(),
+      Some(vec![
+        PortBinding {
+          host_ip: Some("0.0.0.0".to_string()),
+          host_port: Some("27018".to_string()),
+        },
+        PortBinding {
+          host_ip: Some("::".to_string()),
+          host_port: Some("27018".to_string()),
+        },
+      ]),
+    )]);
+
+    assert_eq!(
+      port_bindings_from_docker(&ports, 27017),
+      vec![MongoDBPortBinding {
+        binding_type: BindingType::AnyInterface,
+        ip: "0.0.0.0".to_string(),
+        port: Some(27018),
+      }]
+    );
+    assert!(port_bindings_from_docker(&ports, 27027).is_empty());
+  }
+
+  #[test]
+  fn test_add_port_bindings() {
+    let mut body = ContainerCreateBody::default();
+    add_port_bindings(
+      &mut body,
+      27017,
+      &[
+        binding(BindingType::LoopbackIpv6, ""),
+        MongoDBPortBinding {
+          binding_type: BindingType::Specific,
+          ip: "192.0.2.0".to_string(),
+          port: Some(27019),
+        },
+      ],
+      Some(27018),
+    );
+
+    assert_eq!(body.exposed_ports, Some(vec!["27017/tcp".to_string()]));
+    assert_eq!(
+      body.host_config.unwrap().port_bindings.unwrap()["27017/tcp"],
+      Some(vec![
+        PortBinding {
+          host_ip: Some("::1".to_string()),
+          host_port: Some("27018".to_string()),
+        },
+        PortBinding {
+          host_ip: Some("192.0.2.0".to_string()),
+          host_port: Some("27019".to_string()),
+        },
+      ])
+    );
+  }
+
+  #[test]
+  fn test_pin_port() {
+    let mut body = ContainerCreateBody::default();
+    add_port_bindings(
+      &mut body,
+      27017,
+      &[binding(BindingType::Loopback, "")],
+      None,
+    );
+
+    pin_port(&mut body, 27017, 27018);
+
+    assert_eq!(
+      body.host_config.unwrap().port_bindings.unwrap()["27017/tcp"],
+      Some(vec![PortBinding {
+        host_ip: Some("127.0.0.1".to_string()),
+        host_port: Some("27018".to_string()),
+      }])
+    );
+  }
+
+  #[test]
+  fn test_is_port_free() {
+    let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+    let port = listener.local_addr().unwrap().port();
+
+    assert!(!is_port_free(IpAddr::V4(Ipv4Addr::LOCALHOST), port));
+    // Not an address of this host
+    assert!(is_port_free("192.0.2.1".parse().unwrap(), port));
+  }
+
+  #[test]
+  fn test_listening_socket_inodes() {
+    let table = "  sl  local_address rem_address   st tx_queue rx_queue tr tm->when retrnsmt   uid  timeout inode
+   0: 0100007F:6989 00000000:0000 0A 00000000:00000000 00:00000000 00000000  1000        0 4242 1 0000000000000000 100 0 0 10 0
+   1: 0100007F:6989 0100007F:C350 01 00000000:00000000 00:00000000 00000000  1000        0 4343 1 0000000000000000 20 4 30 10 -1
+   2: 00000000:0016 00000000:0000 0A 00000000:00000000 00:00000000 00000000     0        0 4444 1 0000000000000000 100 0 0 10 0";
+
+    assert_eq!(listening_socket_inodes(table, 27017), vec![4242]);
+    assert_eq!(listening_socket_inodes(table, 22), vec![4444]);
+    assert!(listening_socket_inodes(table, 27018).is_empty());
+  }
+
+  #[test]
+  fn test_socket_inode() {
+    assert_eq!(socket_inode("socket:[4242]"), Some(4242));
+    assert_eq!(socket_inode("pipe:[4242]"), None);
+    assert_eq!(socket_inode("/dev/null"), None);
+  }
+
+  #[test]
+  fn test_parse_lsof_output() {
+    assert_eq!(
+      parse_lsof_output("p4242\ncmongod\n"),
+      Some(PortProcess {
+        pid: 4242,
+        name: "mongod".to_string(),
+      })
+    );
+    assert_eq!(parse_lsof_output(""), None);
+  }
+
+  #[cfg(target_os = "linux")]
+  #[test]
+  fn test_process_using_port() {
+    let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+    let port = listener.local_addr().unwrap().port();
+
+    let process = process_using_port(port).unwrap();
+    assert_eq!(process.pid, std::process::id());
+  }
+
+  #[test]
+  fn test_first_free_port() {
+    let range = PortRange {
+      from: 27017,
+      to: 27020,
+    };
+
+    assert_eq!(first_free_port(range, |port| port > 27018).unwrap(), 27019);
+    assert!(first_free_port(range, |_| false).is_err());
+    assert!(first_free_port(
+      PortRange {
+        from: 27020,
+        to: 27017
+      },
+      |_| true
+    )
+    .is_err());
+  }
+}
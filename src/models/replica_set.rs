@@ -0,0 +1,156 @@
+use std::collections::HashMap;
+
+use anyhow::{Context, Result};
+use serde_json::Value;
+
+use crate::models::list_deployments::Deployment;
+
+// mongod refuses to shrink the oplog below 990 megabytes
+pub(crate) const MIN_OPLOG_SIZE_MB: u32 = 990;
+
+// Label recording the oplog size a deployment was created with
+pub(crate) const OPLOG_SIZE_LABEL: &str = "mongodb-atlas-local.oplog-size-mb";
+
+pub(crate) fn validate_oplog_size(oplog_size_mb: u32) -> Result<()> {
+  if oplog_size_mb < MIN_OPLOG_SIZE_MB {
+    anyhow::bail!("oplogSizeMB must be at least {MIN_OPLOG_SIZE_MB}, got {oplog_size_mb}");
+  }
+
+  Ok(())
+}
+
+pub(crate) fn oplog_size_labels(oplog_size_mb: u32) -> HashMap<String, String> {
+  HashMap::from([(OPLOG_SIZE_LABEL.to_string(), oplog_size_mb.to_string())])
+}
+
+// Only known for deployments created with `oplogSizeMB`
+pub(crate) fn apply_oplog_size_label(
+  deployment: &mut Deployment,
+  labels: &HashMap<String, String>,
+) {
+  deployment.oplog_size_mb = labels
+    .get(OPLOG_SIZE_LABEL)
+    .and_then(|value| value.parse().ok());
+}
+
+pub(crate) fn resize_oplog_command(oplog_size_mb: u32) -> String {
+  format!("db.adminCommand({{ replSetResizeOplog: 1, size: {oplog_size_mb} }})")
+}
+
+// Name of the replica set from the `hello` command response
+pub(crate) fn replica_set_name(hello: &Value) -> Result<String> {
+  hello
+    .get("setName")
+    .and_then(Value::as_str)
+    .map(str::to_string)
+    .context("deployment is not running as a replica set")
+}
+
+// Change streams need a replica set, transactions need one with sessions as well
+pub(crate) fn supports_change_streams(hello: &Value) -> bool {
+  hello.get("setName").is_some()
+}
+
+pub(crate) fn supports_transactions(hello: &Value) -> bool {
+  supports_change_streams(hello) && hello.get("logicalSessionTimeoutMinutes").is_some()
+}
+
+// Adds an option to the query string of a connection string
+pub(crate) fn with_query_option(connection_string: &str, key: &str, value: &str) -> String {
+  if connection_string.contains('?') {
+    let separator = if connection_string.ends_with('?') {
+      ""
+    } else {
+      "&"
+    };
+    return format!("{connection_string}{separator}{key}={value}");
+  }
+
+  // The query string has to follow the path, which is empty after the hosts
+  let hosts_end = connection_string
+    .split_once("://")
+    .map(|(scheme, rest)| scheme.len() + 3 + rest.find('/').unwrap_or(rest.len()));
+  match hosts_end {
+    Some(hosts_end) if hosts_end == connection_string.len() => {
+      format!("{connection_string}/?{key}={value}")
+    }
+    _ => format!("{connection_string}?{key}={value}"),
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use serde_json::json;
+
+  use crate::models::list_deployments::test_deployment;
+
+  use super::*;
+
+  #[test]
+  fn test_validate_oplog_size() {
+    assert!(validate_oplog_size(990).is_ok());
+    assert!(validate_oplog_size(2048).is_ok());
+    assert!(validate_oplog_size(100).is_err());
+  }
+
+  #[test]
+  fn test_resize_oplog_command() {
+    assert_eq!(
+      resize_oplog_command(2048),
+      "db.adminCommand({ replSetResizeOplog: 1, size: 2048 })"
+    );
+  }
+
+  #[test]
+  fn test_apply_oplog_size_label() {
+    let mut deployment = test_deployment("test_deployment");
+
+    apply_oplog_size_label(&mut deployment, &oplog_size_labels(2048));
+
+    assert_eq!(deployment.oplog_size_mb, Some(2048));
+  }
+
+  #[test]
+  fn test_replica_set_name() {
+    assert_eq!(
+      replica_set_name(&json!({ "isWritablePrimary": true, "setName": "rs-localdev" })).unwrap(),
+      "rs-localdev"
+    );
+    assert!(replica_set_name(&json!({ "isWritablePrimary": true })).is_err());
+  }
+
+  #[test]
+  fn test_supports_change_streams_and_transactions() {
+    let hello = json!({
+      "isWritablePrimary": true,
+      "setName": "rs-localdev",
+      "logicalSessionTimeoutMinutes": 30
+    });
+    assert!(supports_change_streams(&hello));
+    assert!(supports_transactions(&hello));
+
+    let hello = json!({ "isWritablePrimary": true, "logicalSessionTimeoutMinutes": 30 });
+    assert!(!supports_change_streams(&hello));
+    assert!(!supports_transactions(&hello));
+  }
+
+  #[test]
+  fn test_with_query_option() {
+    assert_eq!(
+      with_query_option(
+        "mongodb://127.0.0.1:27017/?directConnection=true",
+        "replicaSet",
+        "rs0"
+      ),
+      "mongodb://127.0.0.1:27017/?directConnection=true&replicaSet=rs0"
+    );
+    assert_eq!(
+      with_query_option("mongodb://127.0.0.1:27017", "replicaSet", "rs0"),
+      "mongodb://127.0.0.1:27017/?replicaSet=rs0"
+    );
+    assert_eq!(
+      with_query_option("mongodb://127.0.0.1:27017/test", "replicaSet", "rs0"),
+      "mongodb://127.0.0.1:27017/test?replicaSet=rs0"
+    );
+  }
+}
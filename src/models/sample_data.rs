@@ -0,0 +1,197 @@
+use std::path::{Path, PathBuf};
+
+use anyhow::{Context, Result};
+use futures_util::TryStreamExt;
+use napi_derive::napi;
+use tokio::io::AsyncWriteExt;
+
+use crate::docker::CONFIG_DIRECTORY;
+
+// Archive with every Atlas sample dataset, the datasets are restored from it selectively
+pub(crate) const SAMPLE_DATA_ARCHIVE_URL: &str =
+  "https://atlas-education.s3.amazonaws.com/sampledata.archive";
+
+// Location of the archive on the host while datasets are loaded into the container
+pub(crate) fn sample_data_archive_path(container: &str) -> PathBuf {
+  std::env::temp_dir().join(format!("atlas-local-sampledata-{container}.archive"))
+}
+
+pub(crate) const SAMPLE_DATASETS: &[&str] = &[
+  "sample_airbnb",
+  "sample_analytics",
+  "sample_geospatial",
+  "sample_guides",
+  "sample_mflix",
+  "sample_restaurants",
+  "sample_supplies",
+  "sample_training",
+  "sample_weatherdata",
+];
+
+// Lists the datasets loaded into the deployment, kept in a volume so it survives recreating the
+// container
+pub(crate) fn sample_datasets_file() -> String {
+  format!("{CONFIG_DIRECTORY}/.sample-datasets")
+}
+
+#[napi(object)]
+#[derive(Debug)]
+pub struct SampleDataOptions {
+  // All datasets by default
+  pub datasets: Option<Vec<String>>,
+  // Creates a dynamic `default` search index on every collection of the datasets
+  pub create_search_indexes: Option<bool>,
+}
+
+#[napi(string_enum)]
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub enum SampleDataStage {
+  Downloading,
+  Restoring,
+  CreatingSearchIndexes,
+  Done,
+}
+
+#[napi(object)]
+#[derive(Clone, PartialEq, Debug)]
+pub struct SampleDataProgress {
+  pub stage: SampleDataStage,
+  pub dataset: Option<String>,
+  // Datasets loaded so far
+  pub completed: u32,
+  pub total: u32,
+}
+
+// Known datasets in the requested order, without duplicates
+pub(crate) fn resolve_datasets(datasets: Option<&[String]>) -> Result<Vec<String>> {
+  let Some(datasets) = datasets else {
+    return Ok(SAMPLE_DATASETS.iter().map(|d| d.to_string()).collect());
+  };
+
+  let mut resolved: Vec<String> = Vec::new();
+  for dataset in datasets {
+    if !SAMPLE_DATASETS.contains(&dataset.as_str()) {
+      anyhow::bail!(
+        "unknown sample dataset {dataset}, expected one of {}",
+        SAMPLE_DATASETS.join(", ")
+      );
+    }
+    if !resolved.contains(dataset) {
+      resolved.push(dataset.clone());
+    }
+  }
+
+  Ok(resolved)
+}
+
+// The archive is downloaded on the host, the container doesn't need network access
+pub(crate) async fn download_archive(path: &Path) -> Result<()> {
+  let response = reqwest::get(SAMPLE_DATA_ARCHIVE_URL)
+    .await
+    .and_then(|response| response.error_for_status())
+    .context("download sample data")?;
+
+  let mut file = tokio::fs::File::create(path)
+    .await
+    .context("create sample data archive")?;
+  let mut chunks = response.bytes_stream();
+  while let Some(chunk) = chunks.try_next().await.context("download sample data")? {
+    file
+      .write_all(&chunk)
+      .await
+      .context("write sample data archive")?;
+  }
+  file.flush().await.context("write sample data archive")?;
+
+  Ok(())
+}
+
+// mongorestore reading the archive from stdin
+pub(crate) fn restore_command(dataset: &str) -> Vec<String> {
+  vec![
+    "mongorestore".to_string(),
+    "--archive".to_string(),
+    format!("--nsInclude={dataset}.*"),
+    "--drop".to_string(),
+  ]
+}
+
+pub(crate) fn search_indexes_command(dataset: &str) -> String {
+  format!(
+    "db.getSiblingDB({dataset:?}).getCollectionInfos({{ type: \"collection\" }})\
+     .filter(c => !c.name.startsWith(\"system.\"))\
+     .map(c => db.getSiblingDB({dataset:?}).getCollection(c.name)\
+     .createSearchIndex(\"default\", {{ mappings: {{ dynamic: true }} }}))"
+  )
+}
+
+// One dataset per line
+pub(crate) fn parse_sample_datasets(content: &str) -> Vec<String> {
+  let mut datasets: Vec<String> = content
+    .lines()
+    .map(str::trim)
+    .filter(|line| !line.is_empty())
+    .map(str::to_string)
+    .collect();
+  datasets.sort();
+  datasets.dedup();
+
+  datasets
+}
+
+pub(crate) fn format_sample_datasets(datasets: &[String]) -> String {
+  datasets
+    .iter()
+    .map(|dataset| format!("{dataset}\n"))
+    .collect()
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  #[test]
+  fn test_resolve_datasets() {
+    assert_eq!(resolve_datasets(None).unwrap().len(), SAMPLE_DATASETS.len());
+    assert_eq!(
+      resolve_datasets(Some(&[
+        "sample_mflix".to_string(),
+        "sample_airbnb".to_string(),
+        "sample_mflix".to_string(),
+      ]))
+      .unwrap(),
+      vec!["sample_mflix", "sample_airbnb"]
+    );
+    assert!(resolve_datasets(Some(&["sample_unknown".to_string()])).is_err());
+  }
+
+  #[test]
+  fn test_restore_command() {
+    assert_eq!(
+      restore_command("sample_mflix"),
+      vec![
+        "mongorestore",
+        "--archive",
+        "--nsInclude=sample_mflix.*",
+        "--drop"
+      ]
+    );
+  }
+
+  #[test]
+  fn test_search_indexes_command() {
+    assert!(search_indexes_command("sample_mflix")
+      .starts_with("db.getSiblingDB(\"sample_mflix\").getCollectionInfos("));
+  }
+
+  #[test]
+  fn test_sample_datasets_round_trip() {
+    let datasets = vec!["sample_mflix".to_string(), "sample_airbnb".to_string()];
+
+    assert_eq!(
+      parse_sample_datasets(&format_sample_datasets(&datasets)),
+      vec!["sample_airbnb", "sample_mflix"]
+    );
+    assert!(parse_sample_datasets("").is_empty());
+  }
+}
@@ -0,0 +1,186 @@
+use std::collections::HashMap;
+
+use anyhow::{Context, Result};
+use bollard::models::ContainerCreateBody;
+use serde::{Deserialize, Serialize};
+
+pub(crate) const ROOT_PASSWORD_FILE_ENV: &str = "MONGODB_INITDB_ROOT_PASSWORD_FILE";
+
+// Name of the root password among the secrets of a deployment
+pub(crate) const ROOT_PASSWORD_SECRET: &str = "root-password";
+
+// Label recording where the root password was read from, as JSON
+pub(crate) const ROOT_PASSWORD_SOURCE_LABEL: &str = "mongodb-atlas-local.root-password-source";
+
+// Where a secret is read from on the host
+#[derive(Serialize, Deserialize, PartialEq, Debug)]
+#[serde(rename_all = "lowercase")]
+pub(crate) enum SecretSource {
+  File(String),
+  Env(String),
+  // Name passed to the secret provider of the client
+  Provider(String),
+}
+
+// Secrets are kept in a tmpfs, they never reach the disk and don't show up in `docker inspect` like
+// environment variables do
+// The tmpfs is emptied when the container stops, secrets are written again from their source
+pub(crate) const SECRETS_DIRECTORY: &str = "/run/atlas-local-secrets";
+
+pub(crate) fn secret_file(name: &str) -> String {
+  format!("{SECRETS_DIRECTORY}/{name}")
+}
+
+// Any user of the container can create files in it, like in /tmp, secrets are only readable by the
+// user that wrote them
+pub(crate) fn add_secrets_tmpfs(body: &mut ContainerCreateBody) {
+  body
+    .host_config
+    .get_or_insert_with(Default::default)
+    .tmpfs
+    .get_or_insert_with(HashMap::new)
+    .insert(
+      SECRETS_DIRECTORY.to_string(),
+      "rw,noexec,nosuid,size=1m,mode=1777".to_string(),
+    );
+}
+
+pub(crate) fn secret_source_labels(source: &SecretSource) -> Result<HashMap<String, String>> {
+  Ok(HashMap::from([(
+    ROOT_PASSWORD_SOURCE_LABEL.to_string(),
+    serde_json::to_string(source).context("serialize secret source")?,
+  )]))
+}
+
+pub(crate) fn secret_source_from_labels(labels: &HashMap<String, String>) -> Option<SecretSource> {
+  labels
+    .get(ROOT_PASSWORD_SOURCE_LABEL)
+    .and_then(|value| serde_json::from_str(value).ok())
+}
+
+pub(crate) fn secret_source(
+  secret: &str,
+  from_file: Option<String>,
+  from_env: Option<String>,
+  from_provider: Option<String>,
+) -> Result<Option<SecretSource>> {
+  let sources: Vec<SecretSource> = [
+    from_file.map(SecretSource::File),
+    from_env.map(SecretSource::Env),
+    from_provider.map(SecretSource::Provider),
+  ]
+  .into_iter()
+  .flatten()
+  .collect();
+  if sources.len() > 1 {
+    anyhow::bail!("read the {secret} from a single file, environment variable or secret");
+  }
+
+  Ok(sources.into_iter().next())
+}
+
+// A trailing newline, as left by most editors and `echo`, is not part of the secret
+fn trim_secret(secret: String) -> String {
+  secret.trim_end_matches(['\r', '\n']).to_string()
+}
+
+pub(crate) fn read_secret_file(path: &str) -> Result<String> {
+  std::fs::read_to_string(path)
+    .map(trim_secret)
+    .with_context(|| format!("read secret file {path}"))
+}
+
+pub(crate) fn read_secret_env(name: &str) -> Result<String> {
+  std::env::var(name)
+    .map(trim_secret)
+    .with_context(|| format!("read secret from environment variable {name}"))
+}
+
+// Shell command writing stdin to a file only the container user can read
+pub(crate) fn write_secret_command(path: &str) -> Vec<String> {
+  let directory = path.rsplit_once('/').map(|(directory, _)| directory);
+
+  vec![
+    "sh".to_string(),
+    "-c".to_string(),
+    format!(
+      "umask 077 && mkdir -p '{}' && cat > '{path}'",
+      directory.unwrap_or(".")
+    ),
+  ]
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  #[test]
+  fn test_secret_source() {
+    assert_eq!(
+      secret_source("root password", None, Some("PASSWORD".to_string()), None).unwrap(),
+      Some(SecretSource::Env("PASSWORD".to_string()))
+    );
+    assert_eq!(
+      secret_source("root password", None, None, None).unwrap(),
+      None
+    );
+    assert!(secret_source(
+      "root password",
+      Some("/run/password".to_string()),
+      None,
+      Some("password".to_string())
+    )
+    .is_err());
+  }
+
+  #[test]
+  fn test_secret_source_labels() {
+    let source = SecretSource::Provider("atlas-local/root".to_string());
+
+    let labels = secret_source_labels(&source).unwrap();
+
+    assert_eq!(
+      labels[ROOT_PASSWORD_SOURCE_LABEL],
+      r#"{"provider":"atlas-local/root"}"#
+    );
+    assert_eq!(secret_source_from_labels(&labels), Some(source));
+    assert_eq!(secret_source_from_labels(&HashMap::new()), None);
+  }
+
+  #[test]
+  fn test_add_secrets_tmpfs() {
+    let mut body = ContainerCreateBody::default();
+
+    add_secrets_tmpfs(&mut body);
+
+    assert_eq!(
+      body.host_config.unwrap().tmpfs.unwrap()[SECRETS_DIRECTORY],
+      "rw,noexec,nosuid,size=1m,mode=1777"
+    );
+  }
+
+  #[test]
+  fn test_read_secret_file() {
+    let path = std::env::temp_dir().join("atlas-local-secret-test");
+    std::fs::write(&path, "s3cret\n").unwrap();
+
+    let secret = read_secret_file(&path.to_string_lossy());
+    std::fs::remove_file(&path).unwrap();
+
+    assert_eq!(secret.unwrap(), "s3cret");
+    assert!(read_secret_file("/missing/secret").is_err());
+  }
+
+  #[test]
+  fn test_write_secret_command() {
+    assert_eq!(
+      write_secret_command("/run/atlas-local-secrets/root-password"),
+      vec![
+        "sh",
+        "-c",
+        "umask 077 && mkdir -p '/run/atlas-local-secrets' && cat > \
+         '/run/atlas-local-secrets/root-password'"
+      ]
+    );
+  }
+}
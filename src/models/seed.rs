@@ -0,0 +1,231 @@
+use std::fs::File;
+use std::io::{BufRead, BufReader};
+use std::path::Path;
+
+use anyhow::{Context, Result};
+use napi_derive::napi;
+
+// A file on the host imported into the deployment, `archive` is a mongodump archive restored with
+// mongorestore, `ejson` and `csv` are imported into a collection with mongoimport
+#[napi(object)]
+#[derive(Debug)]
+pub struct SeedSource {
+  #[napi(js_name = "type")]
+  pub source_type: SeedType,
+  pub path: String,
+  // Restores only this database from an archive
+  pub database: Option<String>,
+  // The file name without extension by default
+  pub collection: Option<String>,
+  // Whether an archive is gzip compressed, by default when the file name ends with `.gz`
+  pub gzip: Option<bool>,
+  // Takes the CSV field names from the first line, otherwise `fields` names them
+  pub headerline: Option<bool>,
+  pub fields: Option<Vec<String>>,
+  // Drops the collections before importing
+  pub drop: Option<bool>,
+}
+
+#[napi(string_enum = "lowercase")]
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub enum SeedType {
+  Archive,
+  Ejson,
+  Csv,
+}
+
+// Checks a seed source before anything is created
+pub(crate) fn validate_seed_source(source: &SeedSource) -> Result<()> {
+  if !Path::new(&source.path).is_file() {
+    anyhow::bail!("seed file {} doesn't exist", source.path);
+  }
+
+  match source.source_type {
+    SeedType::Archive => {}
+    SeedType::Ejson | SeedType::Csv if source.database.is_none() => {
+      anyhow::bail!("seeding from {} needs a database", source.path);
+    }
+    SeedType::Ejson => {}
+    SeedType::Csv => {
+      if !source.headerline.unwrap_or_default() && source.fields.is_none() {
+        anyhow::bail!("seeding from {} needs headerline or fields", source.path);
+      }
+    }
+  }
+
+  Ok(())
+}
+
+fn collection_name(source: &SeedSource) -> String {
+  source.collection.clone().unwrap_or_else(|| {
+    Path::new(&source.path)
+      .file_stem()
+      .and_then(|stem| stem.to_str())
+      .map(|stem| stem.strip_suffix(".json").unwrap_or(stem).to_string())
+      .unwrap_or_default()
+  })
+}
+
+// mongorestore or mongoimport command reading the file from stdin
+// `json_array` tells whether an EJSON file holds an array instead of one document per line
+pub(crate) fn seed_command(source: &SeedSource, json_array: bool) -> Vec<String> {
+  let mut cmd = Vec::new();
+  match source.source_type {
+    SeedType::Archive => {
+      cmd.extend(["mongorestore".to_string(), "--archive".to_string()]);
+      if source.gzip.unwrap_or_else(|| source.path.ends_with(".gz")) {
+        cmd.push("--gzip".to_string());
+      }
+      if let Some(database) = &source.database {
+        cmd.push(format!("--nsInclude={database}.*"));
+      }
+    }
+    SeedType::Ejson | SeedType::Csv => {
+      cmd.extend([
+        "mongoimport".to_string(),
+        format!("--db={}", source.database.as_deref().unwrap_or_default()),
+        format!("--collection={}", collection_name(source)),
+      ]);
+      if source.source_type == SeedType::Ejson && json_array {
+        cmd.push("--jsonArray".to_string());
+      }
+      if source.source_type == SeedType::Csv {
+        cmd.push("--type=csv".to_string());
+        if source.headerline.unwrap_or_default() {
+          cmd.push("--headerline".to_string());
+        } else if let Some(fields) = &source.fields {
+          cmd.push(format!("--fields={}", fields.join(",")));
+        }
+      }
+    }
+  }
+  if source.drop.unwrap_or_default() {
+    cmd.push("--drop".to_string());
+  }
+
+  cmd
+}
+
+// An EJSON file holds either an array of documents or one document per line, only the bytes up to
+// the first non-whitespace one are read
+pub(crate) fn is_json_array(content: impl BufRead) -> std::io::Result<bool> {
+  for byte in content.bytes() {
+    let byte = byte?;
+    if !byte.is_ascii_whitespace() {
+      return Ok(byte == b'[');
+    }
+  }
+
+  Ok(false)
+}
+
+pub(crate) fn seed_file_is_json_array(source: &SeedSource) -> Result<bool> {
+  File::open(&source.path)
+    .map(BufReader::new)
+    .and_then(is_json_array)
+    .with_context(|| format!("read seed file {}", source.path))
+}
+
+// Seed files can be large archives, they are streamed instead of read into memory
+pub(crate) async fn open_seed_file(source: &SeedSource) -> Result<tokio::fs::File> {
+  tokio::fs::File::open(&source.path)
+    .await
+    .with_context(|| format!("read seed file {}", source.path))
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  fn source(source_type: SeedType, path: &str) -> SeedSource {
+    SeedSource {
+      source_type,
+      path: path.to_string(),
+      database: None,
+      collection: None,
+      gzip: None,
+      headerline: None,
+      fields: None,
+      drop: None,
+    }
+  }
+
+  #[test]
+  fn test_seed_command_archive() {
+    let mut archive = source(SeedType::Archive, "fixtures/dump.archive.gz");
+    archive.database = Some("app".to_string());
+    archive.drop = Some(true);
+
+    assert_eq!(
+      seed_command(&archive, false),
+      vec![
+        "mongorestore",
+        "--archive",
+        "--gzip",
+        "--nsInclude=app.*",
+        "--drop"
+      ]
+    );
+  }
+
+  #[test]
+  fn test_seed_command_ejson() {
+    let mut ejson = source(SeedType::Ejson, "fixtures/users.json");
+    ejson.database = Some("app".to_string());
+
+    assert_eq!(
+      seed_command(&ejson, true),
+      vec![
+        "mongoimport",
+        "--db=app",
+        "--collection=users",
+        "--jsonArray"
+      ]
+    );
+  }
+
+  #[test]
+  fn test_seed_command_csv() {
+    let mut csv = source(SeedType::Csv, "fixtures/orders.csv");
+    csv.database = Some("app".to_string());
+    csv.collection = Some("purchases".to_string());
+    csv.fields = Some(vec!["id".to_string(), "total".to_string()]);
+
+    assert_eq!(
+      seed_command(&csv, false),
+      vec![
+        "mongoimport",
+        "--db=app",
+        "--collection=purchases",
+        "--type=csv",
+        "--fields=id,total"
+      ]
+    );
+  }
+
+  #[test]
+  fn test_validate_seed_source() {
+    let path = std::env::temp_dir().join("atlas-local-seed-test.csv");
+    std::fs::write(&path, "id,total\n1,10\n").unwrap();
+    let mut csv = source(SeedType::Csv, &path.to_string_lossy());
+
+    let without_database = validate_seed_source(&csv);
+    csv.database = Some("app".to_string());
+    let without_fields = validate_seed_source(&csv);
+    csv.headerline = Some(true);
+    let valid = validate_seed_source(&csv);
+    std::fs::remove_file(&path).unwrap();
+
+    assert!(without_database.is_err());
+    assert!(without_fields.is_err());
+    assert!(valid.is_ok());
+    assert!(validate_seed_source(&source(SeedType::Archive, "/missing/dump.archive")).is_err());
+  }
+
+  #[test]
+  fn test_is_json_array() {
+    assert!(is_json_array(&b"  \n[{\"a\": 1}]"[..]).unwrap());
+    assert!(!is_json_array(&b"{\"a\": 1}\n{\"a\": 2}\n"[..]).unwrap());
+    assert!(!is_json_array(&b""[..]).unwrap());
+  }
+}
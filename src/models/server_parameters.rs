@@ -0,0 +1,363 @@
+use std::collections::HashMap;
+
+use anyhow::{Context, Result};
+use serde_json::{Map, Value};
+
+use crate::docker::CONFIG_DIRECTORY;
+use crate::models::list_deployments::Deployment;
+
+// Labels recording the mongod configuration a deployment was created with, as JSON
+pub(crate) const MONGOD_CONFIG_LABEL: &str = "mongodb-atlas-local.mongod-config";
+pub(crate) const SET_PARAMETERS_LABEL: &str = "mongodb-atlas-local.set-parameters";
+
+// `mongodConfig` is given as an object or as mongod.conf YAML
+pub(crate) fn parse_mongod_config(mongod_config: Value) -> Result<Value> {
+  let mongod_config = match mongod_config {
+    Value::String(yaml) => serde_yaml::from_str(&yaml).context("parse mongodConfig YAML")?,
+    mongod_config => mongod_config,
+  };
+  if !mongod_config.is_object() {
+    anyhow::bail!("mongodConfig must be an object");
+  }
+
+  Ok(mongod_config)
+}
+
+// Server parameters mongod is started with, `setParameters` override the `setParameter` section
+// of the mongod configuration
+// They are passed to mongod as `--setParameter` arguments, the other sections go in a config file
+pub(crate) fn server_parameters(
+  mongod_config: Option<&Value>,
+  set_parameters: Option<&HashMap<String, Value>>,
+) -> Result<Map<String, Value>> {
+  let mut parameters = Map::new();
+
+  if let Some(mongod_config) = mongod_config.and_then(Value::as_object) {
+    match mongod_config.get("setParameter") {
+      Some(Value::Object(section)) => parameters.extend(section.clone()),
+      Some(_) => anyhow::bail!("mongodConfig.setParameter must be an object"),
+      None => {}
+    }
+  }
+  if let Some(set_parameters) = set_parameters {
+    parameters.extend(
+      set_parameters
+        .iter()
+        .map(|(key, value)| (key.clone(), value.clone())),
+    );
+  }
+
+  Ok(parameters)
+}
+
+// The sections of the mongod configuration other than `setParameter`
+pub(crate) fn config_sections(mongod_config: Option<&Value>) -> Option<Map<String, Value>> {
+  let mut sections = mongod_config.and_then(Value::as_object)?.clone();
+  sections.remove("setParameter");
+
+  (!sections.is_empty()).then_some(sections)
+}
+
+// File mongod is started with (`--config`) when the mongod configuration has other sections
+// The config volume keeps it when the container is recreated
+pub(crate) fn mongod_config_file() -> String {
+  format!("{CONFIG_DIRECTORY}/atlas-local-mongod.conf")
+}
+
+// The image starts mongod with options of its own, the sections are merged into them so the
+// config file doesn't lose any, `getCmdLineOpts` reports them as a mongod.conf document
+pub(crate) fn merged_mongod_config(
+  command_line_options: &Value,
+  sections: &Map<String, Value>,
+) -> Result<String> {
+  let mut config = command_line_options
+    .get("parsed")
+    .cloned()
+    .unwrap_or_else(|| Value::Object(Map::new()));
+  if let Some(config) = config.as_object_mut() {
+    // mongod is started with a single config file
+    config.remove("config");
+    config.remove("setParameter");
+  }
+  merge(&mut config, &Value::Object(sections.clone()));
+
+  serde_yaml::to_string(&config).context("serialize mongod configuration")
+}
+
+// Objects are merged key by key, any other value replaces the one in `base`
+fn merge(base: &mut Value, overlay: &Value) {
+  match (base, overlay) {
+    (Value::Object(base), Value::Object(overlay)) => {
+      for (key, value) in overlay {
+        merge(base.entry(key.clone()).or_insert(Value::Null), value);
+      }
+    }
+    (base, overlay) => *base = overlay.clone(),
+  }
+}
+
+pub(crate) fn parameter_labels(
+  mongod_config: Option<&Value>,
+  set_parameters: Option<&HashMap<String, Value>>,
+) -> Result<HashMap<String, String>> {
+  let mut labels = HashMap::new();
+  if let Some(mongod_config) = mongod_config {
+    labels.insert(
+      MONGOD_CONFIG_LABEL.to_string(),
+      serde_json::to_string(mongod_config).context("serialize mongodConfig")?,
+    );
+  }
+  if let Some(set_parameters) = set_parameters {
+    labels.insert(
+      SET_PARAMETERS_LABEL.to_string(),
+      serde_json::to_string(set_parameters).context("serialize setParameters")?,
+    );
+  }
+
+  Ok(labels)
+}
+
+pub(crate) fn apply_parameter_labels(
+  deployment: &mut Deployment,
+  labels: &HashMap<String, String>,
+) {
+  deployment.mongod_config = labels
+    .get(MONGOD_CONFIG_LABEL)
+    .and_then(|value| serde_json::from_str(value).ok());
+  deployment.set_parameters = labels
+    .get(SET_PARAMETERS_LABEL)
+    .and_then(|value| serde_json::from_str(value).ok());
+}
+
+// Parameters mongod only accepts at startup, `setParameter` fails for them on a running mongod
+const STARTUP_PARAMETERS: &[&str] = &[
+  "authenticationMechanisms",
+  "disableLogicalSessionCacheRefresh",
+  "disableSplitHorizonIPCheck",
+  "enableLocalhostAuthBypass",
+  "enableTestCommands",
+  "honorSystemUmask",
+  "initialSyncMethod",
+  "logicalSessionRefreshMillis",
+  "maxSessions",
+  "mongotHost",
+  "ocspEnabled",
+  "opensslCipherConfig",
+  "opensslDiffieHellmanParameters",
+  "processUmask",
+  "recoverFromOplogAsStandalone",
+  "replWriterThreadCount",
+  "saslHostName",
+  "saslServiceName",
+  "searchIndexManagementHostAndPort",
+  "skipShardingConfigurationChecks",
+  "takeUnstableCheckpointOnShutdown",
+  "tcpFastOpenClient",
+  "tcpFastOpenQueueSize",
+  "tcpFastOpenServer",
+  "tlsWithholdClientCertificate",
+];
+
+pub(crate) fn validate_runtime_parameters(parameters: &Map<String, Value>) -> Result<()> {
+  if let Some(key) = parameters
+    .keys()
+    .find(|key| STARTUP_PARAMETERS.contains(&key.as_str()))
+  {
+    anyhow::bail!(
+      "{key} can only be set at startup, pass it in setParameters when creating the deployment"
+    );
+  }
+
+  Ok(())
+}
+
+// Strings are passed as is, other values as JSON which mongod parses for document parameters
+pub(crate) fn set_parameter_args(parameters: &Map<String, Value>) -> Vec<String> {
+  parameters
+    .iter()
+    .flat_map(|(key, value)| {
+      let value = match value {
+        Value::String(value) => value.clone(),
+        value => value.to_string(),
+      };
+      ["--setParameter".to_string(), format!("{key}={value}")]
+    })
+    .collect()
+}
+
+// `setParameter` has to be the first field of the command, so the command is written out instead
+// of being serialized from a map
+pub(crate) fn set_parameter_command(parameters: &Map<String, Value>) -> String {
+  let fields: Vec<String> = parameters
+    .iter()
+    .map(|(key, value)| format!("{}: {value}", Value::String(key.clone())))
+    .collect();
+
+  format!(
+    "db.adminCommand({{ setParameter: 1, {} }})",
+    fields.join(", ")
+  )
+}
+
+#[cfg(test)]
+mod tests {
+  use serde_json::json;
+
+  use super::*;
+
+  #[test]
+  fn test_parse_mongod_config_yaml() {
+    let mongod_config = parse_mongod_config(json!("setParameter:\n  notablescan: true\n")).unwrap();
+
+    assert_eq!(
+      mongod_config,
+      json!({ "setParameter": { "notablescan": true } })
+    );
+  }
+
+  #[test]
+  fn test_parse_mongod_config_rejects_scalars() {
+    assert!(parse_mongod_config(json!(42)).is_err());
+    assert!(parse_mongod_config(json!("just a string")).is_err());
+  }
+
+  #[test]
+  fn test_server_parameters() {
+    let mongod_config = json!({
+      "setParameter": { "notablescan": true, "cursorTimeoutMillis": 1000 }
+    });
+    let set_parameters = HashMap::from([("cursorTimeoutMillis".to_string(), json!(5000))]);
+
+    let parameters = server_parameters(Some(&mongod_config), Some(&set_parameters)).unwrap();
+
+    assert_eq!(
+      Value::Object(parameters),
+      json!({ "notablescan": true, "cursorTimeoutMillis": 5000 })
+    );
+  }
+
+  #[test]
+  fn test_server_parameters_ignores_other_sections() {
+    let mongod_config = json!({ "storage": { "wiredTiger": {} } });
+
+    assert!(server_parameters(Some(&mongod_config), None)
+      .unwrap()
+      .is_empty());
+    assert!(server_parameters(Some(&json!({ "setParameter": 1 })), None).is_err());
+  }
+
+  #[test]
+  fn test_config_sections() {
+    let mongod_config = json!({
+      "setParameter": { "notablescan": true },
+      "operationProfiling": { "mode": "all" }
+    });
+
+    assert_eq!(
+      config_sections(Some(&mongod_config)).map(Value::Object),
+      Some(json!({ "operationProfiling": { "mode": "all" } }))
+    );
+    assert_eq!(
+      config_sections(Some(&json!({ "setParameter": { "notablescan": true } }))),
+      None
+    );
+    assert_eq!(config_sections(None), None);
+  }
+
+  #[test]
+  fn test_merged_mongod_config() {
+    let command_line_options = json!({
+      "argv": ["mongod", "--config", "/etc/mongod.conf"],
+      "parsed": {
+        "config": "/etc/mongod.conf",
+        "net": { "bindIp": "*", "port": 27017 },
+        "replication": { "replSetName": "rs-localdev" },
+        "setParameter": { "mongotHost": "localhost:27027" }
+      },
+      "ok": 1
+    });
+    let sections = json!({
+      "net": { "maxIncomingConnections": 100 },
+      "operationProfiling": { "mode": "all" }
+    });
+
+    let config =
+      merged_mongod_config(&command_line_options, sections.as_object().unwrap()).unwrap();
+
+    assert_eq!(
+      serde_yaml::from_str::<Value>(&config).unwrap(),
+      json!({
+        "net": { "bindIp": "*", "port": 27017, "maxIncomingConnections": 100 },
+        "replication": { "replSetName": "rs-localdev" },
+        "operationProfiling": { "mode": "all" }
+      })
+    );
+  }
+
+  #[test]
+  fn test_parameter_labels() {
+    let mongod_config = json!({ "setParameter": { "notablescan": true } });
+    let set_parameters = HashMap::from([("cursorTimeoutMillis".to_string(), json!(5000))]);
+
+    let labels = parameter_labels(Some(&mongod_config), Some(&set_parameters)).unwrap();
+
+    assert_eq!(
+      labels[MONGOD_CONFIG_LABEL],
+      r#"{"setParameter":{"notablescan":true}}"#
+    );
+    assert_eq!(
+      labels[SET_PARAMETERS_LABEL],
+      r#"{"cursorTimeoutMillis":5000}"#
+    );
+    assert!(parameter_labels(None, None).unwrap().is_empty());
+  }
+
+  #[test]
+  fn test_validate_runtime_parameters() {
+    let runtime = Map::from_iter([("notablescan".to_string(), json!(true))]);
+    let startup = Map::from_iter([("maxSessions".to_string(), json!(100))]);
+
+    assert!(validate_runtime_parameters(&runtime).is_ok());
+    assert!(validate_runtime_parameters(&startup).is_err());
+  }
+
+  #[test]
+  fn test_set_parameter_args() {
+    let parameters = Map::from_iter([
+      ("notablescan".to_string(), json!(true)),
+      (
+        "authenticationMechanisms".to_string(),
+        json!("SCRAM-SHA-256"),
+      ),
+      (
+        "logComponentVerbosity".to_string(),
+        json!({ "verbosity": 1 }),
+      ),
+    ]);
+
+    assert_eq!(
+      set_parameter_args(&parameters),
+      [
+        "--setParameter",
+        "authenticationMechanisms=SCRAM-SHA-256",
+        "--setParameter",
+        r#"logComponentVerbosity={"verbosity":1}"#,
+        "--setParameter",
+        "notablescan=true",
+      ]
+    );
+  }
+
+  #[test]
+  fn test_set_parameter_command() {
+    let parameters = Map::from_iter([
+      ("notablescan".to_string(), json!(true)),
+      ("cursorTimeoutMillis".to_string(), json!(5000)),
+    ]);
+
+    assert_eq!(
+      set_parameter_command(&parameters),
+      "db.adminCommand({ setParameter: 1, \"cursorTimeoutMillis\": 5000, \"notablescan\": true })"
+    );
+  }
+}
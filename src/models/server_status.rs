@@ -0,0 +1,226 @@
+use napi_derive::napi;
+use serde::Deserialize;
+use serde_json::Value;
+
+#[napi(object)]
+pub struct ServerStatus {
+  // Identifiers
+  pub host: String,
+  pub process: String,
+  pub version: String,
+
+  // Runtime
+  pub uptime_seconds: f64,
+  pub storage_engine: String,
+
+  // Activity
+  pub connections: ServerStatusConnections,
+  pub opcounters: ServerStatusOpcounters,
+}
+
+#[napi(object)]
+#[derive(Deserialize, PartialEq, Debug)]
+#[serde(rename_all = "camelCase")]
+pub struct ServerStatusConnections {
+  pub current: i64,
+  pub available: i64,
+  pub total_created: i64,
+}
+
+#[napi(object)]
+#[derive(Deserialize, PartialEq, Debug)]
+pub struct ServerStatusOpcounters {
+  pub insert: i64,
+  pub query: i64,
+  pub update: i64,
+  pub delete: i64,
+  pub getmore: i64,
+  pub command: i64,
+}
+
+#[napi(object)]
+pub struct BuildInfo {
+  // Running server
+  pub version: String,
+  pub git_version: String,
+  pub modules: Vec<String>,
+  pub mongot_version: Option<String>,
+
+  // Version reported by the deployment labels
+  pub label_version: String,
+  pub version_matches_label: bool,
+}
+
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct RawServerStatus {
+  host: String,
+  process: String,
+  version: String,
+  uptime: f64,
+  storage_engine: RawStorageEngine,
+  connections: ServerStatusConnections,
+  opcounters: ServerStatusOpcounters,
+}
+
+#[derive(Deserialize)]
+struct RawStorageEngine {
+  name: String,
+}
+
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct RawBuildInfo {
+  version: String,
+  git_version: String,
+  #[serde(default)]
+  modules: Vec<String>,
+}
+
+impl TryFrom<Value> for ServerStatus {
+  type Error = anyhow::Error;
+
+  fn try_from(source: Value) -> Result<Self, Self::Error> {
+    let raw: RawServerStatus = serde_json::from_value(source)?;
+
+    Ok(Self {
+      host: raw.host,
+      process: raw.process,
+      version: raw.version,
+      uptime_seconds: raw.uptime,
+      storage_engine: raw.storage_engine.name,
+      connections: raw.connections,
+      opcounters: raw.opcounters,
+    })
+  }
+}
+
+impl BuildInfo {
+  pub(crate) fn try_from_build_info(
+    source: Value,
+    label_version: String,
+    mongot_version: Option<String>,
+  ) -> anyhow::Result<Self> {
+    let raw: RawBuildInfo = serde_json::from_value(source)?;
+
+    Ok(Self {
+      version_matches_label: raw.version == label_version,
+      version: raw.version,
+      git_version: raw.git_version,
+      modules: raw.modules,
+      mongot_version,
+      label_version,
+    })
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use serde_json::json;
+
+  use super::*;
+
+  #[test]
+  fn test_server_status_from_server_status_command() {
+    let server_status: ServerStatus = json!({
+      "host": "a1b2c3d4e5f6",
+      "version": "8.0.4",
+      "process": "mongod",
+      "pid": 42,
+      "uptime": 120.0,
+      "storageEngine": {
+        "name": "wiredTiger",
+        "supportsCommittedReads": true
+      },
+      "connections": {
+        "current": 5,
+        "available": 838855,
+        "totalCreated": 17,
+        "active": 2
+      },
+      "opcounters": {
+        "insert": 1,
+        "query": 2,
+        "update": 3,
+        "delete": 4,
+        "getmore": 5,
+        "command": 6
+      }
+    })
+    .try_into()
+    .unwrap();
+
+    assert_eq!(server_status.host, "a1b2c3d4e5f6");
+    assert_eq!(server_status.process, "mongod");
+    assert_eq!(server_status.version, "8.0.4");
+    assert_eq!(server_status.uptime_seconds, 120.0);
+    assert_eq!(server_status.storage_engine, "wiredTiger");
+    assert_eq!(
+      server_status.connections,
+      ServerStatusConnections {
+        current: 5,
+        available: 838855,
+        total_created: 17,
+      }
+    );
+    assert_eq!(
+      server_status.opcounters,
+      ServerStatusOpcounters {
+        insert: 1,
+        query: 2,
+        update: 3,
+        delete: 4,
+        getmore: 5,
+        command: 6,
+      }
+    );
+  }
+
+  #[test]
+  fn test_server_status_from_invalid_server_status_command() {
+    let server_status: Result<ServerStatus, _> = json!({ "ok": 0 }).try_into();
+    assert!(server_status.is_err());
+  }
+
+  #[test]
+  fn test_build_info_from_build_info_command() {
+    let build_info = BuildInfo::try_from_build_info(
+      json!({
+        "version": "8.0.4",
+        "gitVersion": "bc35ab4305d9920d9d8b8ff4b6d1ef3c0be3d9b5",
+        "modules": ["enterprise"],
+        "ok": 1
+      }),
+      "8.0.4".to_string(),
+      Some("1.44.0".to_string()),
+    )
+    .unwrap();
+
+    assert_eq!(build_info.version, "8.0.4");
+    assert_eq!(
+      build_info.git_version,
+      "bc35ab4305d9920d9d8b8ff4b6d1ef3c0be3d9b5"
+    );
+    assert_eq!(build_info.modules, vec!["enterprise".to_string()]);
+    assert_eq!(build_info.mongot_version, Some("1.44.0".to_string()));
+    assert_eq!(build_info.label_version, "8.0.4");
+    assert!(build_info.version_matches_label);
+  }
+
+  #[test]
+  fn test_build_info_from_build_info_command_label_mismatch() {
+    let build_info = BuildInfo::try_from_build_info(
+      json!({
+        "version": "8.0.5",
+        "gitVersion": "bc35ab4305d9920d9d8b8ff4b6d1ef3c0be3d9b5"
+      }),
+      "8.0.4".to_string(),
+      None,
+    )
+    .unwrap();
+
+    assert!(build_info.modules.is_empty());
+    assert_eq!(build_info.mongot_version, None);
+    assert!(!build_info.version_matches_label);
+  }
+}
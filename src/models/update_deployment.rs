@@ -0,0 +1,298 @@
+use std::collections::HashMap;
+
+use anyhow::Result;
+use bollard::models::{ContainerCreateBody, ContainerUpdateBody, PortBinding};
+use napi_derive::napi;
+
+use crate::docker::{LOCAL_DEPLOYMENT_LABEL, MONGODB_PORT};
+use crate::models::auth::set_env;
+use crate::models::list_deployments::{Deployment, MongoDBPortBinding};
+use crate::models::ports::add_port_bindings;
+
+// Labels set by atlas-local besides the ones starting with `mongodb-atlas-local`
+const ATLAS_LOCAL_LABELS: &[&str] = &["mongodb-type", "version"];
+
+// Settings of an existing deployment that can be changed
+// Resource limits alone are changed on the running container, the rest recreates it which
+// restarts mongod
+#[napi(object)]
+pub struct DeploymentPatch {
+  pub voyage_api_key: Option<String>,
+  pub do_not_track: Option<bool>,
+  pub telemetry_base_url: Option<String>,
+  pub resources: Option<ResourceLimits>,
+  // Added to the labels of the container, existing labels are kept
+  // Labels of atlas-local and this library can't be changed
+  pub labels: Option<HashMap<String, String>>,
+  pub mongodb_port_binding: Option<MongoDBPortBinding>,
+}
+
+#[napi(object)]
+pub struct ResourceLimits {
+  pub memory_bytes: Option<i64>,
+  pub cpus: Option<f64>,
+}
+
+#[napi(object)]
+pub struct UpdateDeploymentResult {
+  pub restarted: bool,
+  pub deployment: Deployment,
+}
+
+// atlas-local and the expiration, spec and configuration labels of this library are read back
+// from the container, a patch overwriting them would change what `Deployment` reports
+pub(crate) fn validate_patch(patch: &DeploymentPatch) -> Result<()> {
+  if let Some(key) = patch
+    .labels
+    .iter()
+    .flatten()
+    .map(|(key, _)| key)
+    .find(|key| {
+      key.starts_with(LOCAL_DEPLOYMENT_LABEL) || ATLAS_LOCAL_LABELS.contains(&key.as_str())
+    })
+  {
+    anyhow::bail!("label {key} is reserved");
+  }
+
+  Ok(())
+}
+
+// Environment variables the patch changes, as read by the atlas-local image
+pub(crate) fn env_changes(patch: &DeploymentPatch) -> Vec<(&'static str, String)> {
+  let mut env = Vec::new();
+  if let Some(voyage_api_key) = &patch.voyage_api_key {
+    env.push(("VOYAGE_API_KEY", voyage_api_key.clone()));
+  }
+  if let Some(do_not_track) = patch.do_not_track {
+    env.push(("DO_NOT_TRACK", do_not_track.to_string()));
+  }
+  if let Some(telemetry_base_url) = &patch.telemetry_base_url {
+    env.push(("TELEMETRY_BASE_URL", telemetry_base_url.clone()));
+  }
+
+  env
+}
+
+// Docker can only change resource limits without recreating the container
+pub(crate) fn needs_recreate(patch: &DeploymentPatch) -> bool {
+  !env_changes(patch).is_empty() || patch.labels.is_some() || patch.mongodb_port_binding.is_some()
+}
+
+pub(crate) fn resources_update(resources: &ResourceLimits) -> ContainerUpdateBody {
+  ContainerUpdateBody {
+    memory: resources.memory_bytes,
+    // Swap is unlimited, otherwise a previous swap limit can be lower than the new memory limit
+    memory_swap: resources.memory_bytes.map(|_| -1),
+    nano_cpus: resources.cpus.map(|cpus| (cpus * 1e9) as i64),
+    ..Default::default()
+  }
+}
+
+// Applies the patch to the configuration of a new container
+// Resource limits are part of it so a failed recreate leaves the old limits on the old container
+// A port binding without a port keeps `current_port`
+pub(crate) fn apply_patch(
+  body: &mut ContainerCreateBody,
+  patch: &DeploymentPatch,
+  current_port: Option<u16>,
+) {
+  let env_changes = env_changes(patch);
+  if !env_changes.is_empty() {
+    let env = body.env.get_or_insert_with(Vec::new);
+    for (key, value) in env_changes {
+      set_env(env, key, &value);
+    }
+  }
+
+  if let Some(labels) = &patch.labels {
+    body
+      .labels
+      .get_or_insert_with(HashMap::new)
+      .extend(labels.clone());
+  }
+
+  if let Some(resources) = &patch.resources {
+    let update = resources_update(resources);
+    let host_config = body.host_config.get_or_insert_with(Default::default);
+    if update.memory.is_some() {
+      host_config.memory = update.memory;
+      host_config.memory_swap = update.memory_swap;
+    }
+    if update.nano_cpus.is_some() {
+      host_config.nano_cpus = update.nano_cpus;
+    }
+  }
+
+  // The first binding of the MongoDB port is the one atlas-local reports, the ones after it are
+  // `additionalPortBindings` and stay published
+  if let Some(binding) = &patch.mongodb_port_binding {
+    let key = format!("{MONGODB_PORT}/tcp");
+    let additional: Vec<PortBinding> = body
+      .host_config
+      .as_mut()
+      .and_then(|host_config| host_config.port_bindings.as_mut())
+      .and_then(|port_bindings| port_bindings.remove(&key))
+      .flatten()
+      .into_iter()
+      .flatten()
+      .skip(1)
+      .collect();
+    add_port_bindings(
+      body,
+      MONGODB_PORT,
+      std::slice::from_ref(binding),
+      current_port,
+    );
+    if let Some(published) = body
+      .host_config
+      .as_mut()
+      .and_then(|host_config| host_config.port_bindings.as_mut())
+      .and_then(|port_bindings| port_bindings.get_mut(&key))
+      .and_then(Option::as_mut)
+    {
+      published.extend(additional);
+    }
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use bollard::models::HostConfig;
+
+  use super::*;
+  use crate::models::list_deployments::BindingType;
+
+  fn patch() -> DeploymentPatch {
+    DeploymentPatch {
+      voyage_api_key: None,
+      do_not_track: None,
+      telemetry_base_url: None,
+      resources: None,
+      labels: None,
+      mongodb_port_binding: None,
+    }
+  }
+
+  #[test]
+  fn test_needs_recreate() {
+    let mut limits = patch();
+    limits.resources = Some(ResourceLimits {
+      memory_bytes: Some(1 << 30),
+      cpus: None,
+    });
+    let mut voyage_api_key = patch();
+    voyage_api_key.voyage_api_key = Some("new_key".to_string());
+
+    assert!(!needs_recreate(&limits));
+    assert!(needs_recreate(&voyage_api_key));
+  }
+
+  #[test]
+  fn test_validate_patch() {
+    let mut team = patch();
+    team.labels = Some(HashMap::from([("team".to_string(), "search".to_string())]));
+    let mut expires_at = patch();
+    expires_at.labels = Some(HashMap::from([(
+      "mongodb-atlas-local.expires-at".to_string(),
+      "0".to_string(),
+    )]));
+    let mut version = patch();
+    version.labels = Some(HashMap::from([(
+      "version".to_string(),
+      "8.0.4".to_string(),
+    )]));
+
+    let mut mongodb_type = patch();
+    mongodb_type.labels = Some(HashMap::from([(
+      "mongodb-type".to_string(),
+      "enterprise".to_string(),
+    )]));
+    // atlas-local keeps the creation source in the environment, the label is free to use
+    let mut creation_source = patch();
+    creation_source.labels = Some(HashMap::from([(
+      "creationSource".to_string(),
+      "ci".to_string(),
+    )]));
+
+    assert!(validate_patch(&team).is_ok());
+    assert!(validate_patch(&expires_at).is_err());
+    assert!(validate_patch(&version).is_err());
+    assert!(validate_patch(&mongodb_type).is_err());
+    assert!(validate_patch(&creation_source).is_ok());
+  }
+
+  #[test]
+  fn test_resources_update() {
+    let update = resources_update(&ResourceLimits {
+      memory_bytes: Some(1 << 30),
+      cpus: Some(1.5),
+    });
+
+    assert_eq!(update.memory, Some(1 << 30));
+    assert_eq!(update.memory_swap, Some(-1));
+    assert_eq!(update.nano_cpus, Some(1_500_000_000));
+  }
+
+  #[test]
+  fn test_apply_patch() {
+    let mut body = ContainerCreateBody {
+      env: Some(vec![
+        "VOYAGE_API_KEY=old_key".to_string(),
+        "DO_NOT_TRACK=false".to_string(),
+      ]),
+      host_config: Some(HostConfig {
+        port_bindings: Some(HashMap::from([(
+          "27017/tcp".to_string(),
+          Some(vec![
+            PortBinding {
+              host_ip: Some("127.0.0.1".to_string()),
+              host_port: Some("27017".to_string()),
+            },
+            PortBinding {
+              host_ip: Some("127.0.0.1".to_string()),
+              host_port: Some("27018".to_string()),
+            },
+          ]),
+        )])),
+        ..Default::default()
+      }),
+      ..Default::default()
+    };
+    let mut update = patch();
+    update.resources = Some(ResourceLimits {
+      memory_bytes: Some(1 << 30),
+      cpus: None,
+    });
+    update.voyage_api_key = Some("new_key".to_string());
+    update.labels = Some(HashMap::from([("team".to_string(), "search".to_string())]));
+    update.mongodb_port_binding = Some(MongoDBPortBinding {
+      binding_type: BindingType::AnyInterface,
+      ip: "0.0.0.0".to_string(),
+      port: None,
+    });
+
+    apply_patch(&mut body, &update, Some(27017));
+
+    assert_eq!(
+      body.env.unwrap(),
+      vec!["DO_NOT_TRACK=false", "VOYAGE_API_KEY=new_key"]
+    );
+    assert_eq!(body.labels.unwrap()["team"], "search");
+    let host_config = body.host_config.unwrap();
+    assert_eq!(host_config.memory, Some(1 << 30));
+    assert_eq!(host_config.nano_cpus, None);
+    assert_eq!(
+      host_config.port_bindings.unwrap()["27017/tcp"],
+      Some(vec![
+        PortBinding {
+          host_ip: Some("0.0.0.0".to_string()),
+          host_port: Some("27017".to_string()),
+        },
+        PortBinding {
+          host_ip: Some("127.0.0.1".to_string()),
+          host_port: Some("27018".to_string()),
+        },
+      ])
+    );
+  }
+}
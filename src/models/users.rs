@@ -0,0 +1,339 @@
+use anyhow::{Context, Result};
+use napi_derive::napi;
+use serde_json::{json, Map, Value};
+
+// Database users are created in when none is given, like Atlas database users
+const DEFAULT_USER_DATABASE: &str = "admin";
+
+#[napi(object)]
+#[derive(Clone, PartialEq, Debug)]
+pub struct RoleReference {
+  pub role: String,
+  pub db: String,
+}
+
+#[napi(object)]
+pub struct CreateUserOptions {
+  pub username: String,
+  pub password: String,
+  pub roles: Vec<RoleReference>,
+  // `admin` by default
+  pub database: Option<String>,
+}
+
+#[napi(object)]
+pub struct UpdateUserOptions {
+  pub password: Option<String>,
+  // Replaces the roles of the user
+  pub roles: Option<Vec<RoleReference>>,
+  pub database: Option<String>,
+}
+
+#[napi(object)]
+pub struct ListUsersOptions {
+  // Users of every database by default
+  pub database: Option<String>,
+}
+
+#[napi(object)]
+#[derive(Clone, PartialEq, Debug)]
+pub struct DatabaseUser {
+  pub username: String,
+  pub database: String,
+  pub roles: Vec<RoleReference>,
+}
+
+#[napi(object)]
+pub struct Privilege {
+  pub database: String,
+  // Empty for every collection of the database
+  pub collection: Option<String>,
+  pub actions: Vec<String>,
+}
+
+#[napi(object)]
+pub struct CreateRoleOptions {
+  pub role: String,
+  pub privileges: Vec<Privilege>,
+  // Roles the role inherits from
+  pub roles: Option<Vec<RoleReference>>,
+  // `admin` by default
+  pub database: Option<String>,
+}
+
+fn user_database(database: Option<&str>) -> &str {
+  database.unwrap_or(DEFAULT_USER_DATABASE)
+}
+
+fn roles_value(roles: &[RoleReference]) -> Value {
+  roles
+    .iter()
+    .map(|role| json!({ "role": role.role, "db": role.db }))
+    .collect()
+}
+
+// mongosh expression running a command against a database, the command name has to come first
+// so the command is written out instead of being serialized from a map
+fn run_command(database: &str, command: &str, name: &str, fields: Map<String, Value>) -> String {
+  let mut expression = format!(
+    "db.getSiblingDB({}).runCommand({{ {command}: {}",
+    Value::String(database.to_string()),
+    Value::String(name.to_string())
+  );
+  for (key, value) in fields {
+    expression.push_str(&format!(", {key}: {value}"));
+  }
+  expression.push_str(" })");
+
+  expression
+}
+
+pub(crate) fn create_user_command(options: &CreateUserOptions) -> String {
+  let fields = Map::from_iter([
+    ("pwd".to_string(), Value::String(options.password.clone())),
+    ("roles".to_string(), roles_value(&options.roles)),
+  ]);
+
+  run_command(
+    user_database(options.database.as_deref()),
+    "createUser",
+    &options.username,
+    fields,
+  )
+}
+
+pub(crate) fn update_user_command(username: &str, options: &UpdateUserOptions) -> Result<String> {
+  let mut fields = Map::new();
+  if let Some(password) = &options.password {
+    fields.insert("pwd".to_string(), Value::String(password.clone()));
+  }
+  if let Some(roles) = &options.roles {
+    fields.insert("roles".to_string(), roles_value(roles));
+  }
+  if fields.is_empty() {
+    anyhow::bail!("update user {username}: nothing to update");
+  }
+
+  Ok(run_command(
+    user_database(options.database.as_deref()),
+    "updateUser",
+    username,
+    fields,
+  ))
+}
+
+pub(crate) fn drop_user_command(username: &str, database: Option<&str>) -> String {
+  run_command(user_database(database), "dropUser", username, Map::new())
+}
+
+pub(crate) fn users_info_command(database: Option<&str>) -> String {
+  match database {
+    Some(database) => format!(
+      "db.getSiblingDB({}).runCommand({{ usersInfo: 1 }})",
+      Value::String(database.to_string())
+    ),
+    None => "db.getSiblingDB(\"admin\").runCommand({ usersInfo: { forAllDBs: true } })".to_string(),
+  }
+}
+
+pub(crate) fn create_role_command(options: &CreateRoleOptions) -> String {
+  let privileges: Value = options
+    .privileges
+    .iter()
+    .map(|privilege| {
+      json!({
+        "resource": {
+          "db": privilege.database,
+          "collection": privilege.collection.clone().unwrap_or_default(),
+        },
+        "actions": privilege.actions,
+      })
+    })
+    .collect();
+  let fields = Map::from_iter([
+    ("privileges".to_string(), privileges),
+    (
+      "roles".to_string(),
+      roles_value(options.roles.as_deref().unwrap_or_default()),
+    ),
+  ]);
+
+  run_command(
+    user_database(options.database.as_deref()),
+    "createRole",
+    &options.role,
+    fields,
+  )
+}
+
+// `runCommand` reports failures in the response instead of throwing
+pub(crate) fn check_command_response(response: Value) -> Result<Value> {
+  if response.get("ok").and_then(Value::as_f64) == Some(1.0) {
+    return Ok(response);
+  }
+
+  let message = response
+    .get("errmsg")
+    .and_then(Value::as_str)
+    .unwrap_or("command failed");
+  anyhow::bail!("{message}")
+}
+
+// Users from the response of the `usersInfo` command
+pub(crate) fn parse_users_info(response: &Value) -> Result<Vec<DatabaseUser>> {
+  let users = response
+    .get("users")
+    .and_then(Value::as_array)
+    .context("usersInfo response has no users")?;
+
+  users
+    .iter()
+    .map(|user| {
+      let field = |name: &str| {
+        user
+          .get(name)
+          .and_then(Value::as_str)
+          .map(str::to_string)
+          .with_context(|| format!("user has no {name}"))
+      };
+      let roles = user
+        .get("roles")
+        .and_then(Value::as_array)
+        .into_iter()
+        .flatten()
+        .filter_map(|role| {
+          Some(RoleReference {
+            role: role.get("role")?.as_str()?.to_string(),
+            db: role.get("db")?.as_str()?.to_string(),
+          })
+        })
+        .collect();
+
+      Ok(DatabaseUser {
+        username: field("user")?,
+        database: field("db")?,
+        roles,
+      })
+    })
+    .collect()
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  fn role(role: &str, db: &str) -> RoleReference {
+    RoleReference {
+      role: role.to_string(),
+      db: db.to_string(),
+    }
+  }
+
+  #[test]
+  fn test_create_user_command() {
+    let options = CreateUserOptions {
+      username: "app".to_string(),
+      password: "secret".to_string(),
+      roles: vec![role("readWrite", "app")],
+      database: None,
+    };
+
+    assert_eq!(
+      create_user_command(&options),
+      "db.getSiblingDB(\"admin\").runCommand({ createUser: \"app\", pwd: \"secret\", \
+       roles: [{\"db\":\"app\",\"role\":\"readWrite\"}] })"
+    );
+  }
+
+  #[test]
+  fn test_update_user_command() {
+    let options = UpdateUserOptions {
+      password: None,
+      roles: Some(vec![role("read", "app")]),
+      database: Some("app".to_string()),
+    };
+
+    assert_eq!(
+      update_user_command("app", &options).unwrap(),
+      "db.getSiblingDB(\"app\").runCommand({ updateUser: \"app\", \
+       roles: [{\"db\":\"app\",\"role\":\"read\"}] })"
+    );
+    assert!(update_user_command(
+      "app",
+      &UpdateUserOptions {
+        password: None,
+        roles: None,
+        database: None,
+      }
+    )
+    .is_err());
+  }
+
+  #[test]
+  fn test_users_info_command() {
+    assert_eq!(
+      users_info_command(Some("app")),
+      "db.getSiblingDB(\"app\").runCommand({ usersInfo: 1 })"
+    );
+    assert_eq!(
+      users_info_command(None),
+      "db.getSiblingDB(\"admin\").runCommand({ usersInfo: { forAllDBs: true } })"
+    );
+  }
+
+  #[test]
+  fn test_create_role_command() {
+    let options = CreateRoleOptions {
+      role: "reporting".to_string(),
+      privileges: vec![Privilege {
+        database: "app".to_string(),
+        collection: Some("orders".to_string()),
+        actions: vec!["find".to_string()],
+      }],
+      roles: None,
+      database: None,
+    };
+
+    assert_eq!(
+      create_role_command(&options),
+      "db.getSiblingDB(\"admin\").runCommand({ createRole: \"reporting\", \
+       privileges: [{\"actions\":[\"find\"],\
+       \"resource\":{\"collection\":\"orders\",\"db\":\"app\"}}], \
+       roles: [] })"
+    );
+  }
+
+  #[test]
+  fn test_parse_users_info() {
+    let response = json!({
+      "users": [{
+        "_id": "admin.app",
+        "user": "app",
+        "db": "admin",
+        "roles": [{ "role": "readWrite", "db": "app" }],
+      }],
+      "ok": 1,
+    });
+
+    assert_eq!(
+      parse_users_info(&response).unwrap(),
+      vec![DatabaseUser {
+        username: "app".to_string(),
+        database: "admin".to_string(),
+        roles: vec![role("readWrite", "app")],
+      }]
+    );
+    assert!(parse_users_info(&json!({ "ok": 1 })).is_err());
+  }
+
+  #[test]
+  fn test_check_command_response() {
+    assert!(check_command_response(json!({ "ok": 1 })).is_ok());
+    assert_eq!(
+      check_command_response(json!({ "ok": 0, "errmsg": "User \"app@admin\" already exists" }))
+        .unwrap_err()
+        .to_string(),
+      "User \"app@admin\" already exists"
+    );
+  }
+}